@@ -0,0 +1,76 @@
+//! Atomic rules: which pieces a capture blows up, and why touching kings
+//! are never in check.
+
+use crate::bitboard::Bitboard;
+use crate::board::{Board, Color, Piece, PieceKind, Position};
+
+impl Board {
+    /// Squares emptied by a capture on `target`: the target itself, which
+    /// the capturing piece lands on and explodes with, and every piece
+    /// except pawns on the eight squares around it.
+    pub fn explosion(&self, target: Position) -> Bitboard {
+        let mut exploded = Bitboard::default();
+        exploded.set(target.to_64_index());
+
+        let around = self.piece_attacks(Piece::new(PieceKind::King, self.turn), target.to_index());
+        for index in around.iter() {
+            if let Some(piece) = self.piece_at(Position::from_64_index(index))
+                && piece.kind != PieceKind::Pawn
+            {
+                exploded.set(index);
+            }
+        }
+        exploded
+    }
+
+    /// Whether the kings stand next to each other. In atomic this shields
+    /// both from check, since capturing the enemy king would also blow up
+    /// one's own.
+    pub fn kings_touch(&self) -> bool {
+        let (Some(white), Some(black)) = (self.king(Color::White), self.king(Color::Black)) else {
+            return false;
+        };
+        let (white, black) = (white.to_64_index() as isize, black.to_64_index() as isize);
+        (white % 8 - black % 8).abs() <= 1 && (white / 8 - black / 8).abs() <= 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::variant::Variant;
+
+    fn squares(names: &[&str]) -> Bitboard {
+        let mut bitboard = Bitboard::default();
+        for name in names {
+            bitboard.set(name.parse::<Position>().unwrap().to_64_index());
+        }
+        bitboard
+    }
+
+    #[test]
+    fn test_explosion() {
+        let board = Board::from_fen("4k3/8/2nrb3/2pq4/2NB4/8/8/4K3 w - - 0 1").unwrap();
+
+        // Capturing on d5 takes the knights, rook, bishops and queen with
+        // it but spares the c5 pawn.
+        assert_eq!(
+            board.explosion(Position::from_64_index(35)),
+            squares(&["c4", "d4", "d5", "c6", "d6", "e6"])
+        );
+    }
+
+    #[test]
+    fn test_touching_kings_are_not_in_check() {
+        let fen = "8/8/8/8/3qk3/4K3/8/8 w - - 0 1";
+        let board = Board::from_fen(fen).unwrap();
+        assert!(board.kings_touch());
+        assert!(!board.checkers().is_empty());
+
+        let board = Board::from_variant_fen(fen, Variant::Atomic).unwrap();
+        assert!(board.checkers().is_empty());
+
+        let apart = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert!(!apart.kings_touch());
+    }
+}
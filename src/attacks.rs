@@ -0,0 +1,422 @@
+use crate::bitboard::Bitboard;
+use crate::board::{Board, Color, Piece, PieceKind, Position, Square};
+use crate::shared::{SquareIndex, index_120_to_64};
+use crate::variant::Variant;
+
+// Steps on the 120-square mailbox; off-board squares stop every ray.
+pub const KNIGHT_DIRECTIONS: [isize; 8] = [-8, -19, -21, -12, 8, 19, 21, 12];
+pub const ROOK_DIRECTIONS: [isize; 4] = [-1, -10, 1, 10];
+pub const BISHOP_DIRECTIONS: [isize; 4] = [-9, -11, 11, 9];
+pub const KING_DIRECTIONS: [isize; 8] = [-1, -10, 1, 10, -9, -11, 11, 9];
+
+/// Directions in which a pawn of `color` captures.
+pub fn pawn_capture_directions(color: Color) -> [isize; 2] {
+    match color {
+        Color::White => [9, 11],
+        Color::Black => [-9, -11],
+    }
+}
+
+/// Squares strictly between `a` and `b` when they share a rank, file or
+/// diagonal; empty otherwise.
+pub fn squares_between(a: Position, b: Position) -> Bitboard {
+    let mut between = Bitboard::default();
+    let (from, to) = (a.to_64_index() as isize, b.to_64_index() as isize);
+    let (files, ranks) = (to % 8 - from % 8, to / 8 - from / 8);
+    if !(files == 0 || ranks == 0 || files.abs() == ranks.abs()) || from == to {
+        return between;
+    }
+
+    let direction = ranks.signum() * 8 + files.signum();
+    let mut index = from + direction;
+    while index != to {
+        between.set(index as usize);
+        index += direction;
+    }
+    between
+}
+
+#[inline(always)]
+fn step(from: SquareIndex, direction: isize) -> SquareIndex {
+    from.wrapping_add_signed(direction)
+}
+
+impl Board {
+    /// Squares attacked by the piece on `position`, including squares held
+    /// by its own side. Empty if the square is empty.
+    pub fn attacks_from(&self, position: Position) -> Bitboard {
+        match self.piece_at(position) {
+            Some(piece) => self.piece_attacks(piece, position.to_index()),
+            None => Bitboard::default(),
+        }
+    }
+
+    /// Squares `piece` would attack from the mailbox square `from`.
+    pub fn piece_attacks(&self, piece: Piece, from: SquareIndex) -> Bitboard {
+        let mut attacks = Bitboard::default();
+
+        match piece.kind {
+            PieceKind::Pawn => {
+                self.add_steps(&mut attacks, from, &pawn_capture_directions(piece.color))
+            }
+            PieceKind::Knight => self.add_steps(&mut attacks, from, &KNIGHT_DIRECTIONS),
+            PieceKind::King => self.add_steps(&mut attacks, from, &KING_DIRECTIONS),
+            PieceKind::Bishop => self.add_rays(&mut attacks, from, &BISHOP_DIRECTIONS),
+            PieceKind::Rook => self.add_rays(&mut attacks, from, &ROOK_DIRECTIONS),
+            PieceKind::Queen => {
+                self.add_rays(&mut attacks, from, &BISHOP_DIRECTIONS);
+                self.add_rays(&mut attacks, from, &ROOK_DIRECTIONS);
+            }
+        }
+
+        attacks
+    }
+
+    /// Squares of the pieces of colour `by` that attack `position`.
+    pub fn attackers_of(&self, position: Position, by: Color) -> Bitboard {
+        let target = position.to_index();
+        let mut attackers = Bitboard::default();
+
+        let mut check = |index: SquareIndex, kinds: &[PieceKind]| {
+            if let Square::Occupied(piece) = self.squares[index]
+                && piece.color == by
+                && kinds.contains(&piece.kind)
+            {
+                attackers.set(index_120_to_64(index));
+            }
+        };
+
+        // A pawn attacks the target from where the target's own capture
+        // directions, taken for the other colour, point.
+        for direction in pawn_capture_directions(by.opposite()) {
+            check(step(target, direction), &[PieceKind::Pawn]);
+        }
+        for direction in KNIGHT_DIRECTIONS {
+            check(step(target, direction), &[PieceKind::Knight]);
+        }
+        for direction in KING_DIRECTIONS {
+            check(step(target, direction), &[PieceKind::King]);
+        }
+
+        for (directions, kinds) in [
+            (ROOK_DIRECTIONS, [PieceKind::Rook, PieceKind::Queen]),
+            (BISHOP_DIRECTIONS, [PieceKind::Bishop, PieceKind::Queen]),
+        ] {
+            for direction in directions {
+                let mut index = step(target, direction);
+                while matches!(self.squares[index], Square::Empty) {
+                    index = step(index, direction);
+                }
+                check(index, &kinds);
+            }
+        }
+
+        attackers
+    }
+
+    pub fn is_square_attacked(&self, position: Position, by: Color) -> bool {
+        !self.attackers_of(position, by).is_empty()
+    }
+
+    /// Every square attacked by at least one piece of `color`.
+    pub fn attack_map(&self, color: Color) -> Bitboard {
+        let mut attacks = Bitboard::default();
+        for (position, piece) in self.pieces().filter(|(_, piece)| piece.color == color) {
+            attacks |= self.piece_attacks(piece, position.to_index());
+        }
+        attacks
+    }
+
+    /// Pieces of `color` that are pinned to their own king by an enemy
+    /// slider.
+    pub fn pinned_pieces(&self, color: Color) -> Bitboard {
+        match self.king(color) {
+            Some(king) => self.blockers(king, color.opposite(), color),
+            None => Bitboard::default(),
+        }
+    }
+
+    /// Pieces of the side not to move that give check to the side to move.
+    /// None without a royal king, or in atomic while the kings touch.
+    pub fn checkers(&self) -> Bitboard {
+        let Some(king) = self.king(self.turn) else {
+            return Bitboard::default();
+        };
+        if self.variant == Variant::Atomic && self.kings_touch() {
+            return Bitboard::default();
+        }
+        self.attackers_of(king, self.turn.opposite())
+    }
+
+    /// Pieces of the side to move that give discovered check by moving
+    /// off the line between one of their own sliders and the enemy king.
+    pub fn discovered_check_candidates(&self) -> Bitboard {
+        match self.king(self.turn.opposite()) {
+            Some(king) => self.blockers(king, self.turn, self.turn),
+            None => Bitboard::default(),
+        }
+    }
+
+    /// Squares the slider on `position` attacks through the first piece on
+    /// each of its rays, up to and including the second. Empty for other
+    /// pieces.
+    pub fn xray_attacks(&self, position: Position) -> Bitboard {
+        let mut attacks = Bitboard::default();
+        let Some(piece) = self.piece_at(position) else {
+            return attacks;
+        };
+
+        let directions: &[isize] = match piece.kind {
+            PieceKind::Rook => &ROOK_DIRECTIONS,
+            PieceKind::Bishop => &BISHOP_DIRECTIONS,
+            PieceKind::Queen => &KING_DIRECTIONS,
+            _ => &[],
+        };
+
+        for &direction in directions {
+            let mut pieces_seen = 0;
+            let mut to = step(position.to_index(), direction);
+            while pieces_seen < 2 && !matches!(self.squares[to], Square::OffBoard) {
+                if pieces_seen == 1 {
+                    attacks.set(index_120_to_64(to));
+                }
+                if matches!(self.squares[to], Square::Occupied(_)) {
+                    pieces_seen += 1;
+                }
+                to = step(to, direction);
+            }
+        }
+
+        attacks
+    }
+
+    /// Pieces of colour `blockers` that stand alone between the king on
+    /// `king` and a slider of colour `sliders` that would otherwise attack
+    /// it.
+    fn blockers(&self, king: Position, sliders: Color, blockers: Color) -> Bitboard {
+        let mut found = Bitboard::default();
+
+        for (directions, kinds) in [
+            (ROOK_DIRECTIONS, [PieceKind::Rook, PieceKind::Queen]),
+            (BISHOP_DIRECTIONS, [PieceKind::Bishop, PieceKind::Queen]),
+        ] {
+            for direction in directions {
+                let mut candidate = None;
+                let mut index = step(king.to_index(), direction);
+
+                while let Square::Empty | Square::Occupied(_) = self.squares[index] {
+                    if let Square::Occupied(piece) = self.squares[index] {
+                        match candidate {
+                            None if piece.color == blockers => candidate = Some(index),
+                            Some(blocker)
+                                if piece.color == sliders && kinds.contains(&piece.kind) =>
+                            {
+                                found.set(index_120_to_64(blocker));
+                                break;
+                            }
+                            _ => break,
+                        }
+                    }
+                    index = step(index, direction);
+                }
+            }
+        }
+
+        found
+    }
+
+    fn add_steps(&self, attacks: &mut Bitboard, from: SquareIndex, directions: &[isize]) {
+        for &direction in directions {
+            let to = step(from, direction);
+            if !matches!(self.squares[to], Square::OffBoard) {
+                attacks.set(index_120_to_64(to));
+            }
+        }
+    }
+
+    fn add_rays(&self, attacks: &mut Bitboard, from: SquareIndex, directions: &[isize]) {
+        for &direction in directions {
+            let mut to = step(from, direction);
+            loop {
+                match self.squares[to] {
+                    Square::OffBoard => break,
+                    Square::Occupied(_) => {
+                        attacks.set(index_120_to_64(to));
+                        break;
+                    }
+                    Square::Empty => {
+                        attacks.set(index_120_to_64(to));
+                        to = step(to, direction);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(name: &str) -> Position {
+        name.parse().unwrap()
+    }
+
+    fn squares(names: &[&str]) -> Bitboard {
+        let mut bb = Bitboard::default();
+        for name in names {
+            bb.set(position(name).to_64_index());
+        }
+        bb
+    }
+
+    #[test]
+    fn test_attacks_from_start_position() {
+        let board = Board::new();
+
+        assert_eq!(
+            board.attacks_from(position("b1")),
+            squares(&["a3", "c3", "d2"])
+        );
+        assert_eq!(board.attacks_from(position("a1")), squares(&["a2", "b1"]));
+        assert_eq!(board.attacks_from(position("e2")), squares(&["d3", "f3"]));
+        assert_eq!(board.attacks_from(position("e4")), Bitboard::default());
+        assert_eq!(board.attack_map(Color::White).count(), 22);
+    }
+
+    #[test]
+    fn test_slider_attacks_stop_at_blockers() {
+        let board = Board::from_fen("4k3/8/8/1p6/8/3B4/8/R3K3 w - - 0 1").unwrap();
+
+        assert_eq!(
+            board.attacks_from(position("d3")),
+            squares(&["c4", "b5", "e4", "f5", "g6", "h7", "c2", "b1", "e2", "f1"])
+        );
+        assert_eq!(
+            board.attacks_from(position("a1")),
+            squares(&[
+                "a2", "a3", "a4", "a5", "a6", "a7", "a8", "b1", "c1", "d1", "e1"
+            ])
+        );
+    }
+
+    #[test]
+    fn test_attackers_of() {
+        let board = Board::from_fen("4k3/8/2n5/8/3P4/1B6/8/3RK3 b - - 0 1").unwrap();
+
+        assert_eq!(
+            board.attackers_of(position("d5"), Color::White),
+            squares(&["b3"])
+        );
+        assert_eq!(
+            board.attackers_of(position("e5"), Color::White),
+            squares(&["d4"])
+        );
+        assert_eq!(
+            board.attackers_of(position("d4"), Color::Black),
+            squares(&["c6"])
+        );
+        assert_eq!(
+            board.attackers_of(position("d2"), Color::White),
+            squares(&["d1", "e1"])
+        );
+        assert!(!board.is_square_attacked(position("d8"), Color::White));
+        assert!(board.is_square_attacked(position("f7"), Color::White));
+    }
+
+    #[test]
+    fn test_pinned_pieces() {
+        let board = Board::from_fen("4r1k1/5n2/8/1b6/8/3N4/4B3/q3K2R w - - 0 1").unwrap();
+
+        // The e2 bishop is pinned by the e8 rook and the d3 knight is not on
+        // the b5 bishop's diagonal to e1; the f7 knight is pinned by nothing.
+        assert_eq!(board.pinned_pieces(Color::White), squares(&["e2"]));
+        assert_eq!(board.pinned_pieces(Color::Black), Bitboard::default());
+
+        let doubled = Board::from_fen("4r1k1/8/8/8/4N3/4B3/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(doubled.pinned_pieces(Color::White), Bitboard::default());
+
+        let diagonal = Board::from_fen("6k1/8/8/1b6/8/2N5/8/5K2 w - - 0 1").unwrap();
+        assert_eq!(diagonal.pinned_pieces(Color::White), Bitboard::default());
+        let diagonal = Board::from_fen("6k1/8/8/1b6/2N5/8/8/5K2 w - - 0 1").unwrap();
+        assert_eq!(diagonal.pinned_pieces(Color::White), squares(&["c4"]));
+    }
+
+    #[test]
+    fn test_checkers() {
+        let quiet = Board::new();
+        let double = Board::from_fen("4k3/8/3N4/8/8/8/8/4RK2 b - - 0 1").unwrap();
+
+        assert_eq!(quiet.checkers(), Bitboard::default());
+        assert_eq!(double.checkers(), squares(&["d6", "e1"]));
+    }
+
+    #[test]
+    fn test_discovered_check_candidates() {
+        let board = Board::from_fen("4k3/8/8/4N3/8/2B5/8/4RK2 w - - 0 1").unwrap();
+
+        // The knight unmasks the e1 rook; the c3 bishop's diagonal misses e8.
+        assert_eq!(board.discovered_check_candidates(), squares(&["e5"]));
+        assert_eq!(board.pinned_pieces(Color::White), Bitboard::default());
+
+        let black = Board::from_fen("4k3/4n3/8/8/8/8/8/4K3 b - - 0 1").unwrap();
+        assert_eq!(black.discovered_check_candidates(), Bitboard::default());
+    }
+
+    #[test]
+    fn test_xray_attacks() {
+        let board = Board::from_fen("4k3/8/4p3/8/4R1n1/8/8/4K3 w - - 0 1").unwrap();
+
+        // Behind the e6 pawn and the g4 knight; the own king on e1 ends the
+        // ray at the board edge.
+        assert_eq!(
+            board.xray_attacks(position("e4")),
+            squares(&["e7", "e8", "h4"])
+        );
+        assert_eq!(board.xray_attacks(position("g4")), Bitboard::default());
+    }
+
+    #[test]
+    fn test_squares_between() {
+        assert_eq!(
+            squares_between(position("a1"), position("d4")),
+            squares(&["b2", "c3"])
+        );
+        assert_eq!(
+            squares_between(position("e8"), position("e5")),
+            squares(&["e7", "e6"])
+        );
+        assert_eq!(
+            squares_between(position("e1"), position("f1")),
+            Bitboard::default()
+        );
+        assert_eq!(
+            squares_between(position("a1"), position("b3")),
+            Bitboard::default()
+        );
+        assert_eq!(
+            squares_between(position("h1"), position("a2")),
+            Bitboard::default()
+        );
+    }
+
+    #[test]
+    fn test_attackers_match_attack_maps() {
+        let board =
+            Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap();
+
+        for color in [Color::White, Color::Black] {
+            let map = board.attack_map(color);
+            for index in 0..64 {
+                assert_eq!(
+                    map.is_set(index),
+                    board.is_square_attacked(Position::from_64_index(index), color),
+                    "{:?} attacks on {}",
+                    color,
+                    Position::from_64_index(index)
+                );
+            }
+        }
+    }
+}
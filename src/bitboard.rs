@@ -1,12 +1,54 @@
 #![allow(dead_code)]
 
-use std::{fmt::Display, ops::{Add, BitAnd}};
+use std::{
+    fmt::Display,
+    ops::{Add, BitAnd, BitOr, BitOrAssign, Not},
+};
 
-use crate::shared::{SquareIndex, File, Rank, file_rank_to_64_index};
+use crate::shared::{File, Rank, SquareIndex, file_rank_to_64_index};
 
-#[derive(Debug, Default, Copy, Clone)]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
 pub struct Bitboard(pub u64);
 
+pub const EMPTY: Bitboard = Bitboard(0);
+
+/// One mask per file, a to h (bit index = rank * 8 + file).
+pub const FILE_MASKS: [Bitboard; 8] = {
+    let mut masks = [EMPTY; 8];
+    let mut file = 0;
+    while file < 8 {
+        masks[file] = Bitboard(0x0101_0101_0101_0101 << file);
+        file += 1;
+    }
+    masks
+};
+
+/// One mask per rank, 1 to 8.
+pub const RANK_MASKS: [Bitboard; 8] = {
+    let mut masks = [EMPTY; 8];
+    let mut rank = 0;
+    while rank < 8 {
+        masks[rank] = Bitboard(0xff << (rank * 8));
+        rank += 1;
+    }
+    masks
+};
+
+/// The files either side of each file.
+pub const ADJACENT_FILE_MASKS: [Bitboard; 8] = {
+    let mut masks = [EMPTY; 8];
+    let mut file = 0;
+    while file < 8 {
+        if file > 0 {
+            masks[file].0 |= FILE_MASKS[file - 1].0;
+        }
+        if file < 7 {
+            masks[file].0 |= FILE_MASKS[file + 1].0;
+        }
+        file += 1;
+    }
+    masks
+};
 
 impl Bitboard {
     pub fn set(&mut self, index: SquareIndex) -> &mut Self {
@@ -19,6 +61,14 @@ impl Bitboard {
         self
     }
 
+    pub fn is_set(&self, index: SquareIndex) -> bool {
+        self.0 & (1 << index) != 0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
     pub fn count(&self) -> u32 {
         self.0.count_ones()
     }
@@ -28,6 +78,12 @@ impl Bitboard {
         self.clear(pop_index);
         pop_index
     }
+
+    /// Iterates over the set squares, lowest index first.
+    pub fn iter(self) -> impl Iterator<Item = SquareIndex> {
+        let mut bb = self;
+        std::iter::from_fn(move || (!bb.is_empty()).then(|| bb.pop()))
+    }
 }
 
 impl BitAnd for Bitboard {
@@ -39,7 +95,29 @@ impl BitAnd for Bitboard {
     }
 }
 
-impl Display for  Bitboard {
+impl BitOr for Bitboard {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for Bitboard {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl Not for Bitboard {
+    type Output = Self;
+
+    fn not(self) -> Self::Output {
+        Self(!self.0)
+    }
+}
+
+impl Display for Bitboard {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let shift_me: u64 = 1;
         let mut result = String::new();
@@ -49,16 +127,15 @@ impl Display for  Bitboard {
                 let index = file_rank_to_64_index(file.to_char(), rank.to_char());
                 let bb = *self;
                 let bb_shift = shift_me << (index - 1);
-                
+
                 if (bb_shift & bb.0) > 0 {
                     result = result.add("X");
                 } else {
                     result = result.add("-");
-                } 
+                }
             }
 
             result = result.add("\n");
-
         }
 
         writeln!(f, "{}", result)
@@ -1,18 +1,23 @@
 #![allow(dead_code)]
 
-use colored::Colorize;
-use std::{fmt::Display};
+use std::{fmt::Display, str::FromStr};
 
 use crate::shared::{SquareIndex, File, Rank, file_rank_to_120_index};
 use crate::bitboard::Bitboard;
+use crate::zobrist;
+#[cfg(feature = "tui")]
+use crate::render::{BoardRenderer, Style};
+use crate::moves::Move;
+use crate::variant::Variant;
 
 const BRD_SQ_NUM: usize = 120;
 const MAX_SQ_NUM: usize = 64;
 
-#[derive(Debug, Default)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
 pub struct ZobristKey(pub u64);
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PieceKind {
     Pawn,
     Knight,
@@ -23,42 +28,102 @@ pub enum PieceKind {
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Color {
     White,
     Black,
 }
 
 impl PieceKind {
-    pub fn to_unicode(&self, _color: Color) -> &'static str {
+    /// Outline glyphs for white, filled for black.
+    pub fn to_unicode(self, color: Color) -> &'static str {
+        match (self, color) {
+            (PieceKind::Pawn, Color::White) => "♙",
+            (PieceKind::Knight, Color::White) => "♘",
+            (PieceKind::Bishop, Color::White) => "♗",
+            (PieceKind::Rook, Color::White) => "♖",
+            (PieceKind::Queen, Color::White) => "♕",
+            (PieceKind::King, Color::White) => "♔",
+            (PieceKind::Pawn, Color::Black) => "♟",
+            (PieceKind::Knight, Color::Black) => "♞",
+            (PieceKind::Bishop, Color::Black) => "♝",
+            (PieceKind::Rook, Color::Black) => "♜",
+            (PieceKind::Queen, Color::Black) => "♛",
+            (PieceKind::King, Color::Black) => "♚",
+        }
+    }
+
+    pub fn to_char(self) -> char {
         match self {
-            PieceKind::Pawn => "♟",
-            PieceKind::Knight => "♞",
-            PieceKind::Bishop => "♝",
-            PieceKind::Rook => "♜",
-            PieceKind::Queen => "♛",
-            PieceKind::King => "♚",
+            PieceKind::Pawn => 'p',
+            PieceKind::Knight => 'n',
+            PieceKind::Bishop => 'b',
+            PieceKind::Rook => 'r',
+            PieceKind::Queen => 'q',
+            PieceKind::King => 'k',
+        }
+    }
+
+    pub fn from_char(c: char) -> Option<Self> {
+        match c.to_ascii_lowercase() {
+            'p' => Some(PieceKind::Pawn),
+            'n' => Some(PieceKind::Knight),
+            'b' => Some(PieceKind::Bishop),
+            'r' => Some(PieceKind::Rook),
+            'q' => Some(PieceKind::Queen),
+            'k' => Some(PieceKind::King),
+            _ => None,
+        }
+    }
+}
+
+impl Color {
+    pub fn opposite(self) -> Color {
+        match self {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
         }
     }
 }
 
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Piece {
     pub kind: PieceKind,
     pub color: Color,
 }
 
+impl Piece {
+    pub fn new(kind: PieceKind, color: Color) -> Self {
+        Piece { kind, color }
+    }
+
+    /// FEN letter: uppercase for white, lowercase for black.
+    pub fn to_char(self) -> char {
+        match self.color {
+            Color::White => self.kind.to_char().to_ascii_uppercase(),
+            Color::Black => self.kind.to_char(),
+        }
+    }
+
+    pub fn from_char(c: char) -> Option<Self> {
+        let kind = PieceKind::from_char(c)?;
+        let color = if c.is_ascii_uppercase() {
+            Color::White
+        } else {
+            Color::Black
+        };
+        Some(Piece { kind, color })
+    }
+}
+
 impl Display for Piece {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let symbol = self.kind.to_unicode(self.color);
-        let emoji = match self.color {
-            Color::White => symbol.truecolor(255, 255, 255).bold(),
-            Color::Black => symbol.truecolor(0, 0, 0).bold(),
-        };
-        write!(f, "{}", emoji)
+        write!(f, "{}", self.kind.to_unicode(self.color))
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct Position {
     pub file: File,
     pub rank: Rank,
@@ -69,9 +134,22 @@ impl Position {
         Position { file, rank }
     }
 
-    pub fn to_index(&self) -> SquareIndex {
+    pub fn to_index(self) -> SquareIndex {
         file_rank_to_120_index(self.file.to_char(), self.rank.to_char())
     }
+
+    /// Inverse of `to_64_index`.
+    pub fn from_64_index(index: SquareIndex) -> Self {
+        Position {
+            file: File::iter().nth(index % 8).unwrap(),
+            rank: Rank::iter().nth(index / 8).unwrap(),
+        }
+    }
+
+    /// Index into a `Bitboard`: a1 = 0, h1 = 7, a8 = 56.
+    pub fn to_64_index(self) -> SquareIndex {
+        self.rank as SquareIndex * 8 + self.file as SquareIndex
+    }
 }
 
 impl Display for Position {
@@ -80,19 +158,42 @@ impl Display for Position {
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsePositionError(pub String);
+
+impl Display for ParsePositionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid square '{}'", self.0)
+    }
+}
+
+impl std::error::Error for ParsePositionError {}
+
+/// Parses a square name such as `e4`.
+impl FromStr for Position {
+    type Err = ParsePositionError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let mut chars = name.chars();
+        match (
+            chars.next().and_then(File::from_char),
+            chars.next().and_then(Rank::from_char),
+            chars.next(),
+        ) {
+            (Some(file), Some(rank), None) => Ok(Position::new(file, rank)),
+            _ => Err(ParsePositionError(name.to_string())),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default)]
 pub enum Square {
     Empty,
     Occupied(Piece),
+    #[default]
     OffBoard,
 }
 
-impl Default for Square {
-    fn default() -> Self {
-        Square::OffBoard
-    }
-}
-
 #[derive(Debug, Default)]
 pub struct PieceKindCounts {
     pub pawns: u8,
@@ -110,24 +211,107 @@ pub struct ColoredData<T> {
     pub both: T,
 }
 
-#[derive(Debug, Default)]
+#[derive(Copy, Clone, Debug, Default)]
 pub struct ColoredPair<T> {
     pub white: T,
     pub black: T,
 }
 
-#[derive(Debug)]
-pub enum CastlingRight {
-    WhiteKingSide = 1,
-    WhiteQueenSide = 2,
-    BlackKingSide = 4,
-    BlackQueenSide = 8,
+impl<T> ColoredPair<T> {
+    pub fn from_fn(f: impl Fn(Color) -> T) -> Self {
+        ColoredPair {
+            white: f(Color::White),
+            black: f(Color::Black),
+        }
+    }
+
+    pub fn get(&self, color: Color) -> &T {
+        match color {
+            Color::White => &self.white,
+            Color::Black => &self.black,
+        }
+    }
+
+    pub fn get_mut(&mut self, color: Color) -> &mut T {
+        match color {
+            Color::White => &mut self.white,
+            Color::Black => &mut self.black,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CastlingSide {
+    King,
+    Queen,
+}
+
+/// Castling rights stored as the file of the rook each right refers to,
+/// so Chess960 positions with rooks away from the a/h files are handled
+/// the same way as the standard start position.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct CastlingRights {
+    pub white_king_side: Option<File>,
+    pub white_queen_side: Option<File>,
+    pub black_king_side: Option<File>,
+    pub black_queen_side: Option<File>,
+}
+
+impl CastlingRights {
+    pub fn none() -> Self {
+        CastlingRights::default()
+    }
+
+    pub fn standard() -> Self {
+        CastlingRights {
+            white_king_side: Some(File::H),
+            white_queen_side: Some(File::A),
+            black_king_side: Some(File::H),
+            black_queen_side: Some(File::A),
+        }
+    }
+
+    pub fn rook_file(&self, color: Color, side: CastlingSide) -> Option<File> {
+        match (color, side) {
+            (Color::White, CastlingSide::King) => self.white_king_side,
+            (Color::White, CastlingSide::Queen) => self.white_queen_side,
+            (Color::Black, CastlingSide::King) => self.black_king_side,
+            (Color::Black, CastlingSide::Queen) => self.black_queen_side,
+        }
+    }
+
+    pub fn set(&mut self, color: Color, side: CastlingSide, rook_file: Option<File>) {
+        let slot = match (color, side) {
+            (Color::White, CastlingSide::King) => &mut self.white_king_side,
+            (Color::White, CastlingSide::Queen) => &mut self.white_queen_side,
+            (Color::Black, CastlingSide::King) => &mut self.black_king_side,
+            (Color::Black, CastlingSide::Queen) => &mut self.black_queen_side,
+        };
+        *slot = rook_file;
+    }
+
+    pub fn clear_color(&mut self, color: Color) {
+        self.set(color, CastlingSide::King, None);
+        self.set(color, CastlingSide::Queen, None);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == CastlingRights::none()
+    }
+
+    /// Four-bit summary (K = 1, Q = 2, k = 4, q = 8), e.g. for hashing.
+    pub fn bits(&self) -> u8 {
+        (self.white_king_side.is_some() as u8)
+            | (self.white_queen_side.is_some() as u8) << 1
+            | (self.black_king_side.is_some() as u8) << 2
+            | (self.black_queen_side.is_some() as u8) << 3
+    }
 }
 
 #[derive(Debug)]
 pub struct Undo {
-    pub move_: u32,
-    pub castling_rights: u8,
+    pub move_: Move,
+    pub castling_rights: CastlingRights,
     pub en_passant_target: Option<Position>,
     pub fifty_move_counter: u8,
     pub position_key: ZobristKey,
@@ -143,56 +327,244 @@ pub struct Board {
     pub big_pieces: ColoredData<u8>,
     pub major_pieces: ColoredData<u8>,
     pub minor_pieces: ColoredData<u8>,
-    pub kings: ColoredPair<Position>,
+    /// Where each side's king was last placed. Use `king`, which also
+    /// knows when a variant has no royal king.
+    pub kings: ColoredPair<Option<Position>>,
     pub position_key: ZobristKey,
-    pub castling_rights: u8,
+    /// Hash of the pawns alone, used to index the pawn evaluation cache.
+    pub pawn_key: ZobristKey,
+    pub castling_rights: CastlingRights,
     pub fifty_moves: u8,
+    pub variant: Variant,
+    /// Pieces in hand for drop variants, counted by `PieceKind` (pawn to
+    /// queen).
+    pub pockets: ColoredPair<[u8; 5]>,
+    /// Pieces that got there by promotion; in crazyhouse they go back to
+    /// the pocket as pawns when captured.
+    pub promoted: Bitboard,
+    /// Checks each side still has to give to win at three-check.
+    pub checks_remaining: ColoredPair<u8>,
     pub history: Vec<Undo>,
+    /// Half-moves played since the start of the game.
     pub ply: u32,
 }
 
+/// Renders with `BoardRenderer` in the style detected for stdout. Use
+/// the renderer directly for other styles and options.
+#[cfg(feature = "tui")]
 impl Display for Board {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for rank in ('1'..='8').rev() {
-            write!(f, "{} ", rank)?;
+        write!(f, "{}", BoardRenderer::new(Style::detect()).render(self))
+    }
+}
 
-            for file in 'a'..='h' {
-                let index = file_rank_to_120_index(file, rank);
-                let square = self.squares[index];
+/// The FEN, when built without the `tui` renderer.
+#[cfg(not(feature = "tui"))]
+impl Display for Board {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_fen())
+    }
+}
 
-                let file = File::from_char(file).unwrap() as u8;
-                let rank = Rank::from_char(rank).unwrap() as u8;
+impl Default for Board {
+    fn default() -> Self {
+        Board::new()
+    }
+}
 
-                let is_light = (file + rank) % 2 == 0;
+const STANDARD_BACK_RANK: [PieceKind; 8] = [
+    PieceKind::Rook,
+    PieceKind::Knight,
+    PieceKind::Bishop,
+    PieceKind::Queen,
+    PieceKind::King,
+    PieceKind::Bishop,
+    PieceKind::Knight,
+    PieceKind::Rook,
+];
+
+/// Knight placements among the five squares left after the bishops and
+/// queen, indexed by the last digit of the Scharnagl number.
+const CHESS960_KNIGHTS: [(usize, usize); 10] = [
+    (0, 1),
+    (0, 2),
+    (0, 3),
+    (0, 4),
+    (1, 2),
+    (1, 3),
+    (1, 4),
+    (2, 3),
+    (2, 4),
+    (3, 4),
+];
 
-                let (bg_r, bg_g, bg_b) = if is_light {
-                    (180, 180, 180)
-                } else {
-                    (120, 120, 120)
-                };
+impl Board {
+    pub fn new() -> Self {
+        Board::from_back_rank(STANDARD_BACK_RANK)
+    }
 
-                let colored_cell = match square {
-                    Square::Empty => "   ".on_truecolor(bg_r, bg_g, bg_b),
-                    Square::Occupied(piece) => {
-                        let content = format!(" {} ", piece);
-                        content.on_truecolor(bg_r, bg_g, bg_b)
-                    }
-                    Square::OffBoard => "   ".on_truecolor(bg_r, bg_g, bg_b),
-                };
+    /// Builds the Chess960 start position with the given Scharnagl number
+    /// (0..960). Number 518 is the standard start position.
+    pub fn new_960(id: u16) -> Option<Self> {
+        if id >= 960 {
+            return None;
+        }
 
-                write!(f, "{}", colored_cell)?;
-            }
+        let mut back_rank: [Option<PieceKind>; 8] = [None; 8];
+        let mut n = id as usize;
+
+        back_rank[(n % 4) * 2 + 1] = Some(PieceKind::Bishop);
+        n /= 4;
+        back_rank[(n % 4) * 2] = Some(PieceKind::Bishop);
+        n /= 4;
+
+        let empty_files = |back_rank: &[Option<PieceKind>; 8]| -> Vec<usize> {
+            (0..8).filter(|&i| back_rank[i].is_none()).collect()
+        };
+
+        back_rank[empty_files(&back_rank)[n % 6]] = Some(PieceKind::Queen);
+        n /= 6;
+
+        let (first, second) = CHESS960_KNIGHTS[n];
+        let empty = empty_files(&back_rank);
+        back_rank[empty[first]] = Some(PieceKind::Knight);
+        back_rank[empty[second]] = Some(PieceKind::Knight);
 
-            writeln!(f)?;
+        let empty = empty_files(&back_rank);
+        for (i, kind) in [PieceKind::Rook, PieceKind::King, PieceKind::Rook]
+            .into_iter()
+            .enumerate()
+        {
+            back_rank[empty[i]] = Some(kind);
         }
 
-        writeln!(f, "   a  b  c  d  e  f  g  h")?;
-        Ok(())
+        Some(Board::from_back_rank(back_rank.map(|kind| kind.unwrap())))
     }
-}
 
-impl Board {
-    pub fn new() -> Self {
+    pub fn piece_at(&self, position: Position) -> Option<Piece> {
+        match self.squares[position.to_index()] {
+            Square::Occupied(piece) => Some(piece),
+            _ => None,
+        }
+    }
+
+    /// All pieces on the board, a1 to h8.
+    pub fn pieces(&self) -> impl Iterator<Item = (Position, Piece)> + '_ {
+        (0..MAX_SQ_NUM).filter_map(|index| {
+            let position = Position::from_64_index(index);
+            self.piece_at(position).map(|piece| (position, piece))
+        })
+    }
+
+    /// Squares occupied by pieces of `color`.
+    pub fn occupancy(&self, color: Color) -> Bitboard {
+        let mut occupancy = Bitboard::default();
+        for (position, _) in self.pieces().filter(|(_, piece)| piece.color == color) {
+            occupancy.set(position.to_64_index());
+        }
+        occupancy
+    }
+
+    /// The king of `color` that must not be left in check. `None` if the
+    /// side has no king, as in Horde, or kings are ordinary pieces, as in
+    /// antichess.
+    pub fn king(&self, color: Color) -> Option<Position> {
+        if self.variant == Variant::Antichess {
+            return None;
+        }
+
+        *self.kings.get(color)
+    }
+
+    /// Pieces like `piece` in its owner's pocket; always 0 for kings.
+    pub fn pocket(&self, piece: Piece) -> u8 {
+        let pocket = self.pockets.get(piece.color);
+        pocket.get(piece.kind as usize).copied().unwrap_or(0)
+    }
+
+    /// Adds `piece` to its owner's pocket. Returns false if the pocket is
+    /// already full or the piece is a king.
+    pub(crate) fn add_to_pocket(&mut self, piece: Piece) -> bool {
+        let count = self.pocket(piece);
+        if piece.kind == PieceKind::King || count as usize >= zobrist::MAX_POCKET {
+            return false;
+        }
+
+        self.set_pocket(piece, count + 1);
+        true
+    }
+
+    pub(crate) fn set_pocket(&mut self, piece: Piece, count: u8) {
+        let slot = &mut self.pockets.get_mut(piece.color)[piece.kind as usize];
+        self.position_key.0 ^=
+            zobrist::pocket_key(piece, *slot) ^ zobrist::pocket_key(piece, count);
+        *slot = count;
+    }
+
+    /// Sets the checks `color` still has to give, keeping `position_key`
+    /// in sync. Counts above 3 are clamped.
+    pub(crate) fn set_checks_remaining(&mut self, color: Color, count: u8) {
+        let count = count.min(3);
+        let slot = self.checks_remaining.get_mut(color);
+        self.position_key.0 ^= zobrist::check_key(color, *slot) ^ zobrist::check_key(color, count);
+        *slot = count;
+    }
+
+    /// Puts `piece` on an empty square, keeping the king positions, pawn
+    /// bitboards and the piece part of the position and pawn keys in sync.
+    pub(crate) fn put_piece(&mut self, piece: Piece, position: Position) {
+        self.squares[position.to_index()] = Square::Occupied(piece);
+        self.position_key.0 ^= zobrist::piece_key(piece, position.to_64_index());
+
+        match piece.kind {
+            PieceKind::King => match piece.color {
+                Color::White => self.kings.white = Some(position),
+                Color::Black => self.kings.black = Some(position),
+            },
+            PieceKind::Pawn => {
+                let index = position.to_64_index();
+                match piece.color {
+                    Color::White => self.pawns.white.set(index),
+                    Color::Black => self.pawns.black.set(index),
+                };
+                self.pawns.both.set(index);
+                self.pawn_key.0 ^= zobrist::piece_key(piece, index);
+            }
+            _ => {}
+        }
+    }
+
+    /// Empties `position` and returns the piece that stood there, undoing
+    /// what `put_piece` keeps in sync.
+    pub(crate) fn remove_piece(&mut self, position: Position) -> Option<Piece> {
+        let piece = self.piece_at(position)?;
+        self.squares[position.to_index()] = Square::Empty;
+        self.position_key.0 ^= zobrist::piece_key(piece, position.to_64_index());
+
+        match piece.kind {
+            PieceKind::King => {
+                let king = self.kings.get_mut(piece.color);
+                if *king == Some(position) {
+                    *king = None;
+                }
+            }
+            PieceKind::Pawn => {
+                let index = position.to_64_index();
+                match piece.color {
+                    Color::White => self.pawns.white.clear(index),
+                    Color::Black => self.pawns.black.clear(index),
+                };
+                self.pawns.both.clear(index);
+                self.pawn_key.0 ^= zobrist::piece_key(piece, index);
+            }
+            _ => {}
+        }
+        Some(piece)
+    }
+
+    /// An empty board: every playable square is `Empty`, no castling
+    /// rights, white to move and no kings.
+    pub(crate) fn empty() -> Self {
         let mut board = Board {
             squares: [Square::OffBoard; BRD_SQ_NUM],
             turn: Color::White,
@@ -202,16 +574,15 @@ impl Board {
             big_pieces: ColoredData::default(),
             major_pieces: ColoredData::default(),
             minor_pieces: ColoredData::default(),
-            kings: ColoredPair {
-                white: Position::new(File::E, Rank::One),
-                black: Position::new(File::E, Rank::Eight),
-            },
+            kings: ColoredPair::default(),
             position_key: ZobristKey::default(),
-            castling_rights: CastlingRight::WhiteKingSide as u8
-                | CastlingRight::WhiteQueenSide as u8
-                | CastlingRight::BlackKingSide as u8
-                | CastlingRight::BlackQueenSide as u8,
+            pawn_key: ZobristKey::default(),
+            castling_rights: CastlingRights::none(),
             fifty_moves: 0,
+            variant: Variant::Standard,
+            pockets: ColoredPair::default(),
+            promoted: Bitboard::default(),
+            checks_remaining: ColoredPair { white: 3, black: 3 },
             history: Vec::new(),
             ply: 0,
         };
@@ -223,80 +594,51 @@ impl Board {
             }
         }
 
-        let place = |board: &mut Board, file: File, rank: Rank, piece: Piece| {
-            let pos = Position::new(file, rank);
-            let index = pos.to_index() as usize;
-            board.squares[index] = Square::Occupied(piece);
-        };
-
-        let white_back_rank = [
-            (File::A, PieceKind::Rook),
-            (File::B, PieceKind::Knight),
-            (File::C, PieceKind::Bishop),
-            (File::D, PieceKind::Queen),
-            (File::E, PieceKind::King),
-            (File::F, PieceKind::Bishop),
-            (File::G, PieceKind::Knight),
-            (File::H, PieceKind::Rook),
-        ];
-
-        for (file, kind) in white_back_rank {
-            place(
-                &mut board,
-                file,
-                Rank::One,
-                Piece {
-                    kind,
-                    color: Color::White,
-                },
-            );
-        }
+        board
+    }
 
-        for file in File::iter() {
-            place(
-                &mut board,
-                file,
-                Rank::Two,
-                Piece {
-                    kind: PieceKind::Pawn,
-                    color: Color::White,
-                },
-            );
+    /// Sets up a start position with `back_rank` (a to h) mirrored for both
+    /// sides and full castling rights towards the two rooks.
+    fn from_back_rank(back_rank: [PieceKind; 8]) -> Self {
+        let mut board = Board::empty();
+
+        for (color, back, front) in [
+            (Color::White, Rank::One, Rank::Two),
+            (Color::Black, Rank::Eight, Rank::Seven),
+        ] {
+            for (file, kind) in File::iter().zip(back_rank) {
+                board.put_piece(Piece { kind, color }, Position::new(file, back));
+                board.put_piece(
+                    Piece {
+                        kind: PieceKind::Pawn,
+                        color,
+                    },
+                    Position::new(file, front),
+                );
+            }
         }
 
-        let black_back_rank = [
-            (File::A, PieceKind::Rook),
-            (File::B, PieceKind::Knight),
-            (File::C, PieceKind::Bishop),
-            (File::D, PieceKind::Queen),
-            (File::E, PieceKind::King),
-            (File::F, PieceKind::Bishop),
-            (File::G, PieceKind::Knight),
-            (File::H, PieceKind::Rook),
-        ];
+        let files_of = |wanted: PieceKind| {
+            File::iter()
+                .zip(back_rank)
+                .filter(move |&(_, kind)| kind == wanted)
+                .map(|(file, _)| file)
+        };
 
-        for (file, kind) in black_back_rank {
-            place(
-                &mut board,
-                file,
-                Rank::Eight,
-                Piece {
-                    kind,
-                    color: Color::Black,
-                },
-            );
-        }
-
-        for file in File::iter() {
-            place(
-                &mut board,
-                file,
-                Rank::Seven,
-                Piece {
-                    kind: PieceKind::Pawn,
-                    color: Color::Black,
-                },
-            );
+        let king_file = files_of(PieceKind::King).next().unwrap();
+
+        for rook_file in files_of(PieceKind::Rook) {
+            let side = if rook_file > king_file {
+                CastlingSide::King
+            } else {
+                CastlingSide::Queen
+            };
+            board
+                .castling_rights
+                .set(Color::White, side, Some(rook_file));
+            board
+                .castling_rights
+                .set(Color::Black, side, Some(rook_file));
         }
 
         board
@@ -348,6 +690,18 @@ mod tests {
         assert_eq!(Position::new(File::H, Rank::Eight).to_string(), "h8");
     }
 
+    #[test]
+    fn test_position_from_str() {
+        assert_eq!("a1".parse(), Ok(Position::new(File::A, Rank::One)));
+        assert_eq!("e4".parse(), Ok(Position::new(File::E, Rank::Four)));
+        for name in ["", "e", "e9", "i4", "e44", "E4"] {
+            assert_eq!(
+                name.parse::<Position>(),
+                Err(ParsePositionError(name.to_string()))
+            );
+        }
+    }
+
     #[test]
     fn test_board_initialization() {
         let board = Board::new();
@@ -379,11 +733,8 @@ mod tests {
     fn test_board_kings_initialization() {
         let board = Board::new();
 
-        assert_eq!(board.kings.white.file, File::E);
-        assert_eq!(board.kings.white.rank, Rank::One);
-
-        assert_eq!(board.kings.black.file, File::E);
-        assert_eq!(board.kings.black.rank, Rank::Eight);
+        assert_eq!(board.kings.white, Some(Position::new(File::E, Rank::One)));
+        assert_eq!(board.kings.black, Some(Position::new(File::E, Rank::Eight)));
     }
 
     #[test]
@@ -438,45 +789,27 @@ mod tests {
 
     #[test]
     fn test_piece_display() {
-        fn piece_string(kind: PieceKind, color: Color) -> String {
-            let symbol = kind.to_unicode(color);
-            match color {
-                Color::White => symbol.truecolor(255, 255, 255).bold().to_string(),
-                Color::Black => symbol.truecolor(0, 0, 0).bold().to_string(),
-            }
+        let kinds = [
+            PieceKind::King,
+            PieceKind::Queen,
+            PieceKind::Rook,
+            PieceKind::Bishop,
+            PieceKind::Knight,
+            PieceKind::Pawn,
+        ];
+        for kind in kinds {
+            assert_ne!(
+                Piece::new(kind, Color::White).to_string(),
+                Piece::new(kind, Color::Black).to_string()
+            );
         }
 
-        assert_eq!(
-            piece_string(PieceKind::King, Color::White),
-            piece_string(PieceKind::King, Color::Black)
-        );
-        assert_eq!(
-            piece_string(PieceKind::Queen, Color::White),
-            piece_string(PieceKind::Queen, Color::Black)
-        );
-        assert_eq!(
-            piece_string(PieceKind::Rook, Color::White),
-            piece_string(PieceKind::Rook, Color::Black)
-        );
-        assert_eq!(
-            piece_string(PieceKind::Bishop, Color::White),
-            piece_string(PieceKind::Bishop, Color::Black)
-        );
-        assert_eq!(
-            piece_string(PieceKind::Knight, Color::White),
-            piece_string(PieceKind::Knight, Color::Black)
-        );
-        assert_eq!(
-            piece_string(PieceKind::Pawn, Color::White),
-            piece_string(PieceKind::Pawn, Color::Black)
-        );
-
-        assert_eq!(piece_string(PieceKind::King, Color::White), "♚");
-        assert_eq!(piece_string(PieceKind::Queen, Color::White), "♛");
-        assert_eq!(piece_string(PieceKind::Rook, Color::White), "♜");
-        assert_eq!(piece_string(PieceKind::Bishop, Color::White), "♝");
-        assert_eq!(piece_string(PieceKind::Knight, Color::White), "♞");
-        assert_eq!(piece_string(PieceKind::Pawn, Color::White), "♟");
+        assert_eq!(Piece::new(PieceKind::King, Color::White).to_string(), "♔");
+        assert_eq!(Piece::new(PieceKind::Queen, Color::White).to_string(), "♕");
+        assert_eq!(Piece::new(PieceKind::Pawn, Color::White).to_string(), "♙");
+        assert_eq!(Piece::new(PieceKind::King, Color::Black).to_string(), "♚");
+        assert_eq!(Piece::new(PieceKind::Knight, Color::Black).to_string(), "♞");
+        assert_eq!(Piece::new(PieceKind::Pawn, Color::Black).to_string(), "♟");
     }
 
     #[test]
@@ -485,7 +818,7 @@ mod tests {
 
         let get_piece = |file: File, rank: Rank| {
             let pos = Position::new(file, rank);
-            let index = pos.to_index() as usize;
+            let index = pos.to_index();
             board.squares[index]
         };
 
@@ -629,4 +962,47 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_new_960() {
+        assert_eq!(Board::new_960(518).unwrap().to_fen(), Board::new().to_fen());
+        assert_eq!(
+            Board::new_960(0).unwrap().to_shredder_fen(),
+            "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w HFhf - 0 1"
+        );
+        assert_eq!(
+            Board::new_960(959).unwrap().to_shredder_fen(),
+            "rkrnnqbb/pppppppp/8/8/8/8/PPPPPPPP/RKRNNQBB w CAca - 0 1"
+        );
+        assert!(Board::new_960(960).is_none());
+    }
+
+    #[test]
+    fn test_new_960_kings_and_castling() {
+        let board = Board::new_960(0).unwrap();
+
+        assert_eq!(board.kings.white, Some(Position::new(File::G, Rank::One)));
+        assert_eq!(board.kings.black, Some(Position::new(File::G, Rank::Eight)));
+        assert_eq!(
+            board
+                .castling_rights
+                .rook_file(Color::White, CastlingSide::King),
+            Some(File::H)
+        );
+        assert_eq!(
+            board
+                .castling_rights
+                .rook_file(Color::Black, CastlingSide::Queen),
+            Some(File::F)
+        );
+    }
+
+    #[test]
+    fn test_all_960_positions_are_distinct() {
+        let fens: std::collections::HashSet<String> = (0..960)
+            .map(|id| Board::new_960(id).unwrap().to_fen())
+            .collect();
+
+        assert_eq!(fens.len(), 960);
+    }
 }
@@ -0,0 +1,145 @@
+//! Crazyhouse rules on top of the standard board: what a capture puts in
+//! the pocket and where pieces can be dropped.
+
+use crate::attacks::squares_between;
+use crate::bitboard::Bitboard;
+use crate::board::{Board, Piece, PieceKind, Position};
+use crate::moves::{Move, MoveList};
+
+/// Kinds that can be dropped, in pocket order.
+pub const DROPPABLE: [PieceKind; 5] = [
+    PieceKind::Pawn,
+    PieceKind::Knight,
+    PieceKind::Bishop,
+    PieceKind::Rook,
+    PieceKind::Queen,
+];
+
+// Ranks 2 to 7; pawns are never dropped on the first or last rank.
+const PAWN_DROP_SQUARES: u64 = 0x00ff_ffff_ffff_ff00;
+
+impl Board {
+    /// The piece capturing on `position` would put in its pocket: the
+    /// captured piece with its colour flipped, demoted to a pawn if it was
+    /// promoted. `None` for an empty square or a king.
+    pub fn captured_to_pocket(&self, position: Position) -> Option<Piece> {
+        let piece = self.piece_at(position)?;
+        if piece.kind == PieceKind::King {
+            return None;
+        }
+
+        let kind = if self.promoted.is_set(position.to_64_index()) {
+            PieceKind::Pawn
+        } else {
+            piece.kind
+        };
+        Some(Piece::new(kind, piece.color.opposite()))
+    }
+
+    /// Appends the legal drops of the side to move. In check, only drops
+    /// that block a single sliding checker are legal; there are none
+    /// against a contact or double check.
+    ///
+    /// A full pocket gives more drops than a standard `MoveList` holds, so
+    /// crazyhouse positions need a `CrazyhouseMoveList`.
+    pub fn generate_drops<const N: usize>(&self, list: &mut MoveList<N>) {
+        if !self.variant.has_pockets() {
+            return;
+        }
+
+        let occupied = self.occupancy(self.turn) | self.occupancy(self.turn.opposite());
+        let mut targets = !occupied;
+
+        let checkers = self.checkers();
+        match checkers.count() {
+            0 => {}
+            1 => {
+                let checker = Position::from_64_index(checkers.iter().next().unwrap());
+                let king = self.king(self.turn).expect("checked side has a king");
+                targets = targets & squares_between(king, checker);
+            }
+            _ => return,
+        }
+
+        for kind in DROPPABLE {
+            if self.pocket(Piece::new(kind, self.turn)) == 0 {
+                continue;
+            }
+
+            let squares = match kind {
+                PieceKind::Pawn => targets & Bitboard(PAWN_DROP_SQUARES),
+                _ => targets,
+            };
+            for index in squares.iter() {
+                list.push(Move::drop(kind, Position::from_64_index(index)));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Color;
+    use crate::moves::{CrazyhouseMoveList, MAX_CRAZYHOUSE_MOVES, MAX_MOVES};
+
+    fn drops(fen: &str) -> Vec<String> {
+        let board = Board::from_fen(fen).unwrap();
+        let mut list = CrazyhouseMoveList::default();
+        board.generate_drops(&mut list);
+        list.iter().map(|mv| mv.to_string()).collect()
+    }
+
+    #[test]
+    fn test_drops_on_empty_squares() {
+        let all = drops("4k3/8/8/8/8/8/8/4K3[NP] w - - 0 1");
+
+        // 62 empty squares for the knight, 48 of them on ranks 2 to 7 for
+        // the pawn.
+        assert_eq!(all.iter().filter(|mv| mv.starts_with('N')).count(), 62);
+        assert_eq!(all.iter().filter(|mv| mv.starts_with('P')).count(), 48);
+        assert!(!all.contains(&"P@a1".to_string()));
+        assert!(!all.contains(&"N@e1".to_string()));
+
+        // Black's pocket is empty and standard chess has no drops.
+        assert!(drops("4k3/8/8/8/8/8/8/4K3[NP] b - - 0 1").is_empty());
+        assert!(drops("4k3/8/8/8/8/8/8/4K3 w - - 0 1").is_empty());
+    }
+
+    #[test]
+    fn test_full_pocket_on_empty_board() {
+        let all = drops("4k3/8/8/8/8/8/8/4K3[QRBNP] w - - 0 1");
+
+        // 62 squares for each piece and 48 for the pawn: more than a
+        // standard move list holds.
+        assert_eq!(all.len(), 4 * 62 + 48);
+        assert!(all.len() > MAX_MOVES);
+        assert!(all.len() <= MAX_CRAZYHOUSE_MOVES);
+    }
+
+    #[test]
+    fn test_drops_in_check() {
+        assert_eq!(
+            drops("4r1k1/8/8/8/8/8/8/4K3[Q] w - - 0 1"),
+            vec!["Q@e2", "Q@e3", "Q@e4", "Q@e5", "Q@e6", "Q@e7"]
+        );
+        assert!(drops("4k3/8/8/8/8/5n2/8/4K3[Q] w - - 0 1").is_empty());
+        assert!(drops("4r1k1/8/8/8/8/5n2/8/4K3[Q] w - - 0 1").is_empty());
+    }
+
+    #[test]
+    fn test_captured_to_pocket() {
+        let board = Board::from_fen("4k2Q~/8/8/8/8/8/8/n3K3[] b - - 0 1").unwrap();
+
+        assert_eq!(
+            board.captured_to_pocket(Position::from_64_index(63)),
+            Some(Piece::new(PieceKind::Pawn, Color::Black))
+        );
+        assert_eq!(
+            board.captured_to_pocket(Position::from_64_index(0)),
+            Some(Piece::new(PieceKind::Knight, Color::White))
+        );
+        assert_eq!(board.captured_to_pocket(Position::from_64_index(4)), None);
+        assert_eq!(board.captured_to_pocket(Position::from_64_index(1)), None);
+    }
+}
@@ -0,0 +1,392 @@
+//! Training data records: a position with its evaluation and the game
+//! result, in a fixed 32-byte binary form and as a line of text.
+//!
+//! Binary layout, little endian:
+//!
+//! | bytes  | content                                                   |
+//! |--------|-----------------------------------------------------------|
+//! | 0..8   | occupancy bitboard                                        |
+//! | 8..24  | one nibble per occupied square, in occupancy order        |
+//! | 24     | side to move (bit 0)                                      |
+//! | 25     | en passant square, 64 if none                             |
+//! | 26     | halfmove clock                                            |
+//! | 27     | result for white: 0 loss, 1 draw, 2 win                   |
+//! | 28..30 | evaluation in centipawns, white's point of view (`i16`)   |
+//! | 30..32 | fullmove number (`u16`)                                   |
+//!
+//! Piece nibbles use `zobrist::piece_index` (white 0-5, black 6-11), plus
+//! 12 and 13 for a white or black rook that can still castle, so Chess960
+//! castling rights survive the round trip.
+//!
+//! The text form is `<fen> | <eval> | <result>`, e.g.
+//! `4k3/8/8/8/8/8/8/4K2R w K - 0 1 | 512 | 1.0`.
+
+use std::fmt::Display;
+
+use crate::bitboard::{Bitboard, EMPTY};
+use crate::board::{Board, CastlingSide, Color, Piece, PieceKind, Position};
+use crate::shared::Rank;
+use crate::zobrist::piece_index;
+
+pub const RECORD_SIZE: usize = 32;
+
+const NO_SQUARE: u8 = 64;
+const CASTLING_ROOK: [u8; 2] = [12, 13];
+const KINDS: [PieceKind; 6] = [
+    PieceKind::Pawn,
+    PieceKind::Knight,
+    PieceKind::Bishop,
+    PieceKind::Rook,
+    PieceKind::Queen,
+    PieceKind::King,
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordError {
+    TooManyPieces(usize),
+    InvalidPiece(u8),
+    InvalidSquare(u8),
+    InvalidResult(String),
+    InvalidEval(String),
+    MissingKing(Color),
+    ExtraKing(Color),
+    InvalidCastlingRook(u8),
+    InvalidFen(String),
+    MissingField(&'static str),
+}
+
+impl Display for RecordError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecordError::TooManyPieces(n) => write!(f, "{} pieces do not fit a record", n),
+            RecordError::InvalidPiece(code) => write!(f, "invalid piece code {}", code),
+            RecordError::InvalidSquare(square) => write!(f, "invalid square {}", square),
+            RecordError::InvalidResult(s) => write!(f, "invalid game result '{}'", s),
+            RecordError::InvalidEval(s) => write!(f, "invalid evaluation '{}'", s),
+            RecordError::MissingKing(color) => write!(f, "no {:?} king on the board", color),
+            RecordError::ExtraKing(color) => write!(f, "more than one {:?} king", color),
+            RecordError::InvalidCastlingRook(square) => write!(
+                f,
+                "castling rook on square {} is not on its king's back rank",
+                square
+            ),
+            RecordError::InvalidFen(s) => write!(f, "invalid FEN: {}", s),
+            RecordError::MissingField(field) => write!(f, "missing {} field", field),
+        }
+    }
+}
+
+impl std::error::Error for RecordError {}
+
+/// One training position.
+#[derive(Debug)]
+pub struct Record {
+    pub board: Board,
+    /// Centipawns from white's point of view.
+    pub eval: i16,
+    /// Game result from white's point of view: 1, 0.5 or 0.
+    pub result: f64,
+}
+
+impl Record {
+    pub fn pack(&self) -> Result<[u8; RECORD_SIZE], RecordError> {
+        let board = &self.board;
+        let mut bytes = [0; RECORD_SIZE];
+
+        let pieces: Vec<(Position, Piece)> = {
+            let mut pieces: Vec<_> = board.pieces().collect();
+            pieces.sort_by_key(|(position, _)| position.to_64_index());
+            pieces
+        };
+        if pieces.len() > 32 {
+            return Err(RecordError::TooManyPieces(pieces.len()));
+        }
+
+        let mut occupancy = EMPTY;
+        for (i, (position, piece)) in pieces.iter().enumerate() {
+            occupancy.set(position.to_64_index());
+            let code = if is_castling_rook(board, *position, *piece) {
+                CASTLING_ROOK[piece.color as usize]
+            } else {
+                piece_index(*piece) as u8
+            };
+            bytes[8 + i / 2] |= code << (4 * (i % 2));
+        }
+        bytes[0..8].copy_from_slice(&occupancy.0.to_le_bytes());
+
+        bytes[24] = board.turn as u8;
+        bytes[25] = board
+            .en_passant_target
+            .map_or(NO_SQUARE, |position| position.to_64_index() as u8);
+        bytes[26] = board.fifty_moves;
+        bytes[27] = (self.result * 2.0).round() as u8;
+        bytes[28..30].copy_from_slice(&self.eval.to_le_bytes());
+        let fullmove = (board.ply / 2 + 1).min(u16::MAX as u32) as u16;
+        bytes[30..32].copy_from_slice(&fullmove.to_le_bytes());
+
+        Ok(bytes)
+    }
+
+    pub fn unpack(bytes: &[u8; RECORD_SIZE]) -> Result<Self, RecordError> {
+        let mut board = Board::empty();
+        let occupancy = Bitboard(u64::from_le_bytes(bytes[0..8].try_into().unwrap()));
+
+        for (i, square) in occupancy.iter().enumerate() {
+            let code = (bytes[8 + i / 2] >> (4 * (i % 2))) & 0xf;
+            let position = Position::from_64_index(square);
+            let piece = match code {
+                0..12 => Piece::new(
+                    KINDS[code as usize % 6],
+                    if code < 6 { Color::White } else { Color::Black },
+                ),
+                12 => Piece::new(PieceKind::Rook, Color::White),
+                13 => Piece::new(PieceKind::Rook, Color::Black),
+                _ => return Err(RecordError::InvalidPiece(code)),
+            };
+            board.put_piece(piece, position);
+        }
+
+        for color in [Color::White, Color::Black] {
+            let kings = board
+                .pieces()
+                .filter(|(_, piece)| piece.color == color && piece.kind == PieceKind::King)
+                .count();
+            match kings {
+                0 => return Err(RecordError::MissingKing(color)),
+                1 => {}
+                _ => return Err(RecordError::ExtraKing(color)),
+            }
+        }
+
+        // Castling rooks are only known once the kings are placed.
+        for (i, square) in occupancy.iter().enumerate() {
+            let code = (bytes[8 + i / 2] >> (4 * (i % 2))) & 0xf;
+            if let Some(color) = [Color::White, Color::Black]
+                .into_iter()
+                .find(|&color| CASTLING_ROOK[color as usize] == code)
+            {
+                let position = Position::from_64_index(square);
+                let king = board.king(color).expect("kings checked above");
+                let back_rank = match color {
+                    Color::White => Rank::One,
+                    Color::Black => Rank::Eight,
+                };
+                if king.rank != back_rank || position.rank != back_rank {
+                    return Err(RecordError::InvalidCastlingRook(square as u8));
+                }
+                let side = if position.file > king.file {
+                    CastlingSide::King
+                } else {
+                    CastlingSide::Queen
+                };
+                board.castling_rights.set(color, side, Some(position.file));
+            }
+        }
+
+        board.turn = if bytes[24] & 1 == 0 {
+            Color::White
+        } else {
+            Color::Black
+        };
+        board.en_passant_target = match bytes[25] {
+            NO_SQUARE => None,
+            square @ 0..64 => Some(Position::from_64_index(square as usize)),
+            square => return Err(RecordError::InvalidSquare(square)),
+        };
+        board.fifty_moves = bytes[26];
+
+        let result = match bytes[27] {
+            0 => 0.0,
+            1 => 0.5,
+            2 => 1.0,
+            other => return Err(RecordError::InvalidResult(other.to_string())),
+        };
+        let eval = i16::from_le_bytes([bytes[28], bytes[29]]);
+        let fullmove = u16::from_le_bytes([bytes[30], bytes[31]]) as u32;
+        board.ply = fullmove.saturating_sub(1) * 2 + (board.turn == Color::Black) as u32;
+
+        Ok(Record {
+            board,
+            eval,
+            result,
+        })
+    }
+
+    /// Parses `<fen> | <eval> | <result>`. The result may be `1.0`, `0.5`,
+    /// `0.0` or `1-0`, `1/2-1/2`, `0-1`.
+    pub fn from_text(line: &str) -> Result<Self, RecordError> {
+        let mut fields = line.split('|').map(str::trim);
+        let fen = fields.next().ok_or(RecordError::MissingField("FEN"))?;
+        let eval = fields.next().ok_or(RecordError::MissingField("eval"))?;
+        let result = fields.next().ok_or(RecordError::MissingField("result"))?;
+
+        Ok(Record {
+            board: Board::from_fen(fen).map_err(|err| RecordError::InvalidFen(err.to_string()))?,
+            eval: eval
+                .parse()
+                .map_err(|_| RecordError::InvalidEval(eval.to_string()))?,
+            result: parse_result(result)
+                .ok_or_else(|| RecordError::InvalidResult(result.to_string()))?,
+        })
+    }
+
+    pub fn to_text(&self) -> String {
+        format!(
+            "{} | {} | {:.1}",
+            self.board.to_fen(),
+            self.eval,
+            self.result
+        )
+    }
+}
+
+/// Game result for white from `1.0`, `0.5`, `0.0` or PGN notation.
+pub fn parse_result(result: &str) -> Option<f64> {
+    match result {
+        "1-0" | "1.0" => Some(1.0),
+        "1/2-1/2" | "0.5" => Some(0.5),
+        "0-1" | "0.0" => Some(0.0),
+        _ => None,
+    }
+}
+
+fn is_castling_rook(board: &Board, position: Position, piece: Piece) -> bool {
+    piece.kind == PieceKind::Rook
+        && [CastlingSide::King, CastlingSide::Queen]
+            .into_iter()
+            .any(|side| {
+                board.castling_rights.rook_file(piece.color, side) == Some(position.file)
+                    && board
+                        .king(piece.color)
+                        .is_some_and(|king| king.rank == position.rank)
+            })
+}
+
+/// Converts text records to binary, one per non-empty line.
+pub fn text_to_binary(input: &str) -> Result<Vec<u8>, (usize, RecordError)> {
+    let mut output = Vec::new();
+
+    for (index, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record = Record::from_text(line).map_err(|err| (index + 1, err))?;
+        output.extend_from_slice(&record.pack().map_err(|err| (index + 1, err))?);
+    }
+
+    Ok(output)
+}
+
+/// Converts binary records to text, one line each. Errors carry the
+/// record number.
+pub fn binary_to_text(input: &[u8]) -> Result<String, (usize, RecordError)> {
+    let mut output = String::new();
+
+    for (index, chunk) in input.chunks(RECORD_SIZE).enumerate() {
+        let bytes: &[u8; RECORD_SIZE] = chunk
+            .try_into()
+            .map_err(|_| (index + 1, RecordError::MissingField("record bytes")))?;
+        let record = Record::unpack(bytes).map_err(|err| (index + 1, err))?;
+        output.push_str(&record.to_text());
+        output.push('\n');
+    }
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fen::START_FEN;
+
+    fn round_trip(fen: &str) -> String {
+        let record = Record {
+            board: Board::from_fen(fen).unwrap(),
+            eval: -37,
+            result: 0.5,
+        };
+        let unpacked = Record::unpack(&record.pack().unwrap()).unwrap();
+
+        assert_eq!(unpacked.eval, -37);
+        assert_eq!(unpacked.result, 0.5);
+        unpacked.board.to_shredder_fen()
+    }
+
+    #[test]
+    fn test_pack_round_trip() {
+        for fen in [
+            START_FEN,
+            "r3k2r/8/8/3pP3/8/8/8/R3K2R w Kq d6 4 30",
+            "4k3/8/8/8/8/8/8/4K3 b - - 99 120",
+        ] {
+            let board = Board::from_fen(fen).unwrap();
+            assert_eq!(round_trip(fen), board.to_shredder_fen());
+        }
+    }
+
+    #[test]
+    fn test_pack_round_trip_chess960() {
+        let board = Board::new_960(0).unwrap();
+        assert_eq!(round_trip(&board.to_fen()), board.to_shredder_fen());
+    }
+
+    #[test]
+    fn test_text_round_trip() {
+        let line = "4k3/8/8/8/8/8/8/4K2R w K - 0 1 | 512 | 1.0";
+        let binary = text_to_binary(&format!("{}\n\n{}\n", line, line)).unwrap();
+
+        assert_eq!(binary.len(), 2 * RECORD_SIZE);
+        assert_eq!(
+            binary_to_text(&binary).unwrap(),
+            format!("{}\n{}\n", line, line)
+        );
+    }
+
+    #[test]
+    fn test_record_errors() {
+        assert_eq!(
+            text_to_binary("4k3/8/8/8/8/8/8/4K3 w - - | 0").unwrap_err(),
+            (1, RecordError::MissingField("result"))
+        );
+        assert!(matches!(
+            Record::from_text("4k3/8/8/8/8/8/8/4K3 w - - | x | 1.0"),
+            Err(RecordError::InvalidEval(_))
+        ));
+        assert_eq!(binary_to_text(&[0; 31]).unwrap_err().0, 1);
+        assert_eq!(
+            Record::unpack(&[0; RECORD_SIZE]).unwrap_err(),
+            RecordError::MissingKing(Color::White)
+        );
+    }
+
+    #[test]
+    fn test_unpack_rejects_impossible_boards() {
+        let pack = |fen: &str| {
+            Record {
+                board: Board::from_fen(fen).unwrap(),
+                eval: 0,
+                result: 0.5,
+            }
+            .pack()
+            .unwrap()
+        };
+
+        // Nibbles in square order: e1 king, h1 rook (castling), e8 king.
+        let mut bytes = pack("4k3/8/8/8/8/8/8/4K2R w K - 0 1");
+        assert_eq!(bytes[8], 12 << 4 | 5);
+        // Move the castling rook from h1 to h2.
+        bytes[0..8].copy_from_slice(&(1u64 << 4 | 1 << 15 | 1 << 60).to_le_bytes());
+        assert_eq!(
+            Record::unpack(&bytes).unwrap_err(),
+            RecordError::InvalidCastlingRook(15)
+        );
+
+        // A second white king in place of the black one.
+        let mut bytes = pack("4k3/8/8/8/8/8/8/4K3 w - - 0 1");
+        bytes[8] = 5 << 4 | 5;
+        assert_eq!(
+            Record::unpack(&bytes).unwrap_err(),
+            RecordError::ExtraKing(Color::White)
+        );
+    }
+}
@@ -0,0 +1,341 @@
+pub mod king;
+pub mod material;
+pub mod mobility;
+#[cfg(feature = "nnue")]
+pub mod nnue;
+pub mod params;
+pub mod pawns;
+pub mod pieces;
+
+use std::fmt::Display;
+
+use crate::board::{Board, Color, ColoredPair};
+use params::EvalParams;
+use pawns::PawnTable;
+
+/// Common interface of the evaluation backends, so the handcrafted
+/// `Evaluator` and `nnue::Nnue` can be swapped freely.
+pub trait Evaluate {
+    /// Score in centipawns from the point of view of the side to move.
+    fn evaluate(&mut self, board: &Board) -> i32;
+}
+
+/// One named evaluation term, split by side.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Term {
+    pub name: &'static str,
+    pub white: i32,
+    pub black: i32,
+}
+
+impl Term {
+    /// Net contribution from white's point of view.
+    pub fn total(&self) -> i32 {
+        self.white - self.black
+    }
+}
+
+/// Breakdown of an evaluation into its terms, for debugging.
+#[derive(Clone, Debug, Default)]
+pub struct EvalTrace {
+    pub terms: Vec<Term>,
+}
+
+impl EvalTrace {
+    /// Sum of all terms from white's point of view.
+    pub fn total(&self) -> i32 {
+        self.terms.iter().map(Term::total).sum()
+    }
+
+    pub fn term(&self, name: &str) -> Option<&Term> {
+        self.terms.iter().find(|term| term.name == name)
+    }
+}
+
+impl Display for EvalTrace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{:<18}{:>8}{:>8}{:>8}",
+            "Term", "White", "Black", "Total"
+        )?;
+
+        for term in &self.terms {
+            writeln!(
+                f,
+                "{:<18}{:>8}{:>8}{:>8}",
+                term.name,
+                term.white,
+                term.black,
+                term.total()
+            )?;
+        }
+
+        writeln!(f, "{:<18}{:>24}", "Total (white)", self.total())
+    }
+}
+
+/// Receives the evaluation as a list of features: how many times a weight
+/// in `EvalParams` applies to one side. Scoring, tracing and tuning are all
+/// sinks over the same walk of the board.
+pub trait FeatureSink {
+    fn add(&mut self, color: Color, param: usize, count: i32);
+
+    /// Called after all features of the named term have been added.
+    fn end_term(&mut self, _name: &'static str) {}
+}
+
+/// Sums features times weights, by side.
+#[derive(Debug)]
+pub struct Scorer<'a> {
+    params: &'a EvalParams,
+    pub score: ColoredPair<i32>,
+}
+
+impl<'a> Scorer<'a> {
+    pub fn new(params: &'a EvalParams) -> Self {
+        Scorer {
+            params,
+            score: ColoredPair::default(),
+        }
+    }
+
+    /// Score from white's point of view.
+    pub fn total(&self) -> i32 {
+        self.score.white - self.score.black
+    }
+}
+
+impl FeatureSink for Scorer<'_> {
+    fn add(&mut self, color: Color, param: usize, count: i32) {
+        *self.score.get_mut(color) += self.params[param] * count;
+    }
+}
+
+/// Scorer that splits the running score into terms.
+struct Tracer<'a> {
+    scorer: Scorer<'a>,
+    recorded: ColoredPair<i32>,
+    trace: EvalTrace,
+}
+
+impl FeatureSink for Tracer<'_> {
+    fn add(&mut self, color: Color, param: usize, count: i32) {
+        self.scorer.add(color, param, count);
+    }
+
+    fn end_term(&mut self, name: &'static str) {
+        let score = self.scorer.score;
+        self.trace.terms.push(Term {
+            name,
+            white: score.white - self.recorded.white,
+            black: score.black - self.recorded.black,
+        });
+        self.recorded = score;
+    }
+}
+
+/// Adds one term's features for both sides.
+fn term<S: FeatureSink>(sink: &mut S, name: &'static str, mut f: impl FnMut(Color, &mut S)) {
+    for color in [Color::White, Color::Black] {
+        f(color, sink);
+    }
+    sink.end_term(name);
+}
+
+/// Walks every evaluation feature of `board`, term by term.
+pub fn visit_features<S: FeatureSink>(board: &Board, pawn_table: &mut PawnTable, sink: &mut S) {
+    term(sink, "Material", |color, sink| {
+        material::material(board, color, sink)
+    });
+    term(sink, "Piece squares", |color, sink| {
+        material::piece_squares(board, color, sink)
+    });
+
+    let pawn_features = pawn_table.probe(board);
+    term(sink, "Pawn structure", |color, sink| {
+        pawn_features.get(color).record(color, sink)
+    });
+    term(sink, "Passed pawns", |color, sink| {
+        pawns::passed_pawns(board, pawn_features.get(color).passed, color, sink)
+    });
+
+    let activity = ColoredPair::from_fn(|color| mobility::evaluate(board, color));
+    term(sink, "Mobility", |color, sink| {
+        mobility::mobility(activity.get(color), color, sink)
+    });
+    term(sink, "King safety", |color, sink| {
+        king::safety(activity.get(color.opposite()), color, sink)
+    });
+    term(sink, "Pawn shield", |color, sink| {
+        king::pawn_shield(board, color, sink)
+    });
+    term(sink, "King open files", |color, sink| {
+        king::open_files(board, color, sink)
+    });
+
+    term(sink, "Bishop pair", |color, sink| {
+        pieces::bishop_pair(board, color, sink)
+    });
+    term(sink, "Rook open files", |color, sink| {
+        pieces::rook_files(board, color, sink)
+    });
+    term(sink, "Rook on seventh", |color, sink| {
+        pieces::rook_on_seventh(board, color, sink)
+    });
+}
+
+/// Handcrafted static evaluation. Holds the weights and the caches that
+/// persist between calls, so one instance should be reused for a whole
+/// search.
+#[derive(Debug, Default)]
+pub struct Evaluator {
+    params: EvalParams,
+    pawn_table: PawnTable,
+}
+
+impl Evaluator {
+    pub fn new() -> Self {
+        Evaluator::default()
+    }
+
+    pub fn with_params(params: EvalParams) -> Self {
+        Evaluator {
+            params,
+            pawn_table: PawnTable::default(),
+        }
+    }
+
+    pub fn params(&self) -> &EvalParams {
+        &self.params
+    }
+
+    /// Every term of the evaluation, by side.
+    pub fn trace(&mut self, board: &Board) -> EvalTrace {
+        let mut tracer = Tracer {
+            scorer: Scorer::new(&self.params),
+            recorded: ColoredPair::default(),
+            trace: EvalTrace::default(),
+        };
+        visit_features(board, &mut self.pawn_table, &mut tracer);
+        tracer.trace
+    }
+}
+
+impl Evaluate for Evaluator {
+    fn evaluate(&mut self, board: &Board) -> i32 {
+        let mut scorer = Scorer::new(&self.params);
+        visit_features(board, &mut self.pawn_table, &mut scorer);
+        let score = scorer.total();
+
+        match board.turn {
+            Color::White => score,
+            Color::Black => -score,
+        }
+    }
+}
+
+/// Helpers shared by the tests of the evaluation terms.
+#[cfg(test)]
+pub(crate) mod test_helpers {
+    use super::Scorer;
+    use super::params::EvalParams;
+
+    /// Runs one term's features through a scorer with the default weights
+    /// and returns what they add, whichever side they are for.
+    pub fn score(f: impl Fn(&mut Scorer)) -> i32 {
+        let params = EvalParams::default();
+        let mut scorer = Scorer::new(&params);
+        f(&mut scorer);
+        scorer.score.white + scorer.score.black
+    }
+
+    /// Default weight of `index`.
+    pub fn param(index: usize) -> i32 {
+        EvalParams::default()[index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIDDLEGAME: &str = "r1bq1rk1/pp2bppp/2n1pn2/3p4/2PP4/2N1PN2/PP3PPP/R2QKB1R w KQ - 0 8";
+
+    #[test]
+    fn test_start_position_is_balanced() {
+        let mut evaluator = Evaluator::new();
+        let trace = evaluator.trace(&Board::new());
+
+        assert_eq!(trace.total(), 0);
+        assert_eq!(evaluator.evaluate(&Board::new()), 0);
+    }
+
+    #[test]
+    fn test_trace_matches_evaluate() {
+        let mut evaluator = Evaluator::new();
+        let board = Board::from_fen(MIDDLEGAME).unwrap();
+        let trace = evaluator.trace(&board);
+
+        assert_eq!(trace.total(), evaluator.evaluate(&board));
+        for name in [
+            "Material",
+            "Piece squares",
+            "Pawn structure",
+            "Passed pawns",
+            "Mobility",
+            "King safety",
+            "Pawn shield",
+            "King open files",
+            "Bishop pair",
+            "Rook open files",
+            "Rook on seventh",
+        ] {
+            assert!(trace.term(name).is_some(), "missing term {}", name);
+        }
+    }
+
+    #[test]
+    fn test_evaluation_is_side_relative() {
+        let mut evaluator = Evaluator::new();
+        let white = Board::from_fen(MIDDLEGAME).unwrap();
+        let black = Board::from_fen(&MIDDLEGAME.replace(" w ", " b ")).unwrap();
+
+        assert_eq!(evaluator.evaluate(&white), -evaluator.evaluate(&black));
+    }
+
+    #[test]
+    fn test_evaluation_is_symmetric() {
+        let mut evaluator = Evaluator::new();
+        let white = Board::from_fen("6k1/5ppp/8/8/3N4/2B5/1P3PPP/R5K1 w - - 0 1").unwrap();
+        let black = Board::from_fen("r5k1/1p3ppp/2b5/3n4/8/8/5PPP/6K1 b - - 0 1").unwrap();
+
+        assert_ne!(evaluator.evaluate(&white), 0);
+        assert_eq!(evaluator.evaluate(&white), evaluator.evaluate(&black));
+    }
+
+    #[test]
+    fn test_material_decides_the_score() {
+        let mut evaluator = Evaluator::new();
+        let queen_up =
+            Board::from_fen("rnb1kbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+
+        assert!(evaluator.evaluate(&queen_up) > 800);
+    }
+
+    #[test]
+    fn test_custom_params() {
+        let board = Board::from_fen("2b1k3/8/8/8/8/8/8/2B1KB2 w - - 0 1").unwrap();
+        let mut params = EvalParams::default();
+        params.0[params::BISHOP_PAIR] += 100;
+
+        let default = Evaluator::new().trace(&board);
+        let custom = Evaluator::with_params(params).trace(&board);
+
+        assert_eq!(
+            custom.term("Bishop pair").unwrap().total(),
+            default.term("Bishop pair").unwrap().total() + 100
+        );
+        assert_eq!(custom.total(), default.total() + 100);
+    }
+}
@@ -0,0 +1,166 @@
+use crate::bitboard::{Bitboard, EMPTY, FILE_MASKS};
+use crate::board::{Board, Color, Position};
+
+use super::FeatureSink;
+use super::mobility::Activity;
+use super::params::{KING_OPEN_FILE, KING_SAFETY, KING_SEMI_OPEN_FILE, PAWN_SHIELD};
+use super::pawns::{pawns_of, relative_rank};
+
+/// A single attacker is not scored; the king safety table applies from
+/// this many attackers on.
+pub const MIN_KING_ATTACKERS: u32 = 2;
+
+/// The king's square and the squares around it.
+const KING_ZONES: [Bitboard; 64] = {
+    let mut zones = [EMPTY; 64];
+    let mut square = 0;
+    while square < 64 {
+        let file = (square % 8) as i32;
+        let rank = (square / 8) as i32;
+        let mut zone = 0u64;
+        let mut df = -1;
+        while df <= 1 {
+            let mut dr = -1;
+            while dr <= 1 {
+                let (f, r) = (file + df, rank + dr);
+                if f >= 0 && f < 8 && r >= 0 && r < 8 {
+                    zone |= 1 << (r * 8 + f);
+                }
+                dr += 1;
+            }
+            df += 1;
+        }
+        zones[square] = Bitboard(zone);
+        square += 1;
+    }
+    zones
+};
+
+pub fn king_zone(king: Position) -> Bitboard {
+    KING_ZONES[king.to_64_index()]
+}
+
+/// Penalty for the pieces attacking `color`'s king, given the opponent's
+/// activity: the king safety table indexed by attack units.
+pub fn safety(enemy: &Activity, color: Color, sink: &mut impl FeatureSink) {
+    if enemy.king_attackers >= MIN_KING_ATTACKERS {
+        let units = enemy.king_attack_units.clamp(0, 99) as usize;
+        sink.add(color, KING_SAFETY + units, -1);
+    }
+}
+
+/// The files of the king and its neighbours.
+fn king_files(king: Position) -> impl Iterator<Item = usize> {
+    let file = king.file as usize;
+    file.saturating_sub(1)..=(file + 1).min(7)
+}
+
+/// Bonus for pawns sheltering a king that is still on its first two ranks.
+pub fn pawn_shield(board: &Board, color: Color, sink: &mut impl FeatureSink) {
+    let Some(king) = board.king(color) else {
+        return;
+    };
+    let king_rank = relative_rank(color, king.to_64_index());
+    if king_rank > 1 {
+        return;
+    }
+
+    let own = pawns_of(board, color);
+
+    for file in king_files(king) {
+        for distance in 0..2 {
+            let rank = king_rank + distance + 1;
+            let square = match color {
+                Color::White => rank * 8 + file,
+                Color::Black => (7 - rank) * 8 + file,
+            };
+            if own.is_set(square) {
+                sink.add(color, PAWN_SHIELD + distance, 1);
+            }
+        }
+    }
+}
+
+/// Penalty for open and half-open files next to the king.
+pub fn open_files(board: &Board, color: Color, sink: &mut impl FeatureSink) {
+    let Some(king) = board.king(color) else {
+        return;
+    };
+    let own = pawns_of(board, color);
+    let enemy = pawns_of(board, color.opposite());
+
+    for file in king_files(king) {
+        if !(own & FILE_MASKS[file]).is_empty() {
+            continue;
+        }
+        if (enemy & FILE_MASKS[file]).is_empty() {
+            sink.add(color, KING_OPEN_FILE, 1);
+        } else {
+            sink.add(color, KING_SEMI_OPEN_FILE, 1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::test_helpers::{param, score};
+    use crate::shared::{File, Rank};
+
+    #[test]
+    fn test_king_zone() {
+        assert_eq!(king_zone(Position::new(File::A, Rank::One)).count(), 4);
+        assert_eq!(king_zone(Position::new(File::E, Rank::One)).count(), 6);
+        assert_eq!(king_zone(Position::new(File::E, Rank::Four)).count(), 9);
+    }
+
+    #[test]
+    fn test_safety_needs_two_attackers() {
+        let lone = Activity {
+            mobility: [0; 6],
+            king_attackers: 1,
+            king_attack_units: 20,
+        };
+        let pair = Activity {
+            king_attackers: 2,
+            ..lone
+        };
+
+        assert_eq!(score(|s| safety(&lone, Color::White, s)), 0);
+        assert_eq!(
+            score(|s| safety(&pair, Color::White, s)),
+            -param(KING_SAFETY + 20)
+        );
+    }
+
+    #[test]
+    fn test_pawn_shield() {
+        let castled = Board::from_fen("6k1/5ppp/8/8/8/6P1/5P1P/6K1 w - - 0 1").unwrap();
+
+        assert_eq!(
+            score(|s| pawn_shield(&castled, Color::White, s)),
+            2 * param(PAWN_SHIELD) + param(PAWN_SHIELD + 1)
+        );
+        assert_eq!(
+            score(|s| pawn_shield(&castled, Color::Black, s)),
+            3 * param(PAWN_SHIELD)
+        );
+
+        let exposed = Board::from_fen("8/5ppp/8/6k1/8/8/5PPP/6K1 w - - 0 1").unwrap();
+        assert_eq!(score(|s| pawn_shield(&exposed, Color::Black, s)), 0);
+    }
+
+    #[test]
+    fn test_open_files_near_king() {
+        let board = Board::from_fen("6k1/5p1p/8/8/8/8/7P/6K1 w - - 0 1").unwrap();
+
+        assert_eq!(
+            score(|s| open_files(&board, Color::White, s)),
+            param(KING_SEMI_OPEN_FILE) + param(KING_OPEN_FILE)
+        );
+        assert_eq!(
+            score(|s| open_files(&board, Color::Black, s)),
+            param(KING_OPEN_FILE)
+        );
+    }
+}
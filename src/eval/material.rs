@@ -0,0 +1,66 @@
+use crate::board::{Board, Color};
+
+use super::FeatureSink;
+use super::params::{MATERIAL, PIECE_SQUARE};
+
+/// `square` seen from `color`'s side of the board, so a1 for white and a8
+/// for black are both 0.
+pub fn relative_square(color: Color, square: usize) -> usize {
+    match color {
+        Color::White => square,
+        Color::Black => square ^ 56,
+    }
+}
+
+/// Piece values, counted for every piece on the board.
+pub fn material(board: &Board, color: Color, sink: &mut impl FeatureSink) {
+    for (_, piece) in board.pieces() {
+        if piece.color == color {
+            sink.add(color, MATERIAL + piece.kind as usize, 1);
+        }
+    }
+}
+
+/// Bonus or penalty for where each piece stands.
+pub fn piece_squares(board: &Board, color: Color, sink: &mut impl FeatureSink) {
+    for (position, piece) in board.pieces() {
+        if piece.color == color {
+            let square = relative_square(color, position.to_64_index());
+            sink.add(color, PIECE_SQUARE + piece.kind as usize * 64 + square, 1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::PieceKind;
+    use crate::eval::test_helpers::{param, score};
+
+    #[test]
+    fn test_material() {
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/3QK3 w - - 0 1").unwrap();
+        let queen = MATERIAL + PieceKind::Queen as usize;
+        let king = MATERIAL + PieceKind::King as usize;
+
+        assert_eq!(
+            score(|s| material(&board, Color::White, s)),
+            param(queen) + param(king)
+        );
+        assert_eq!(score(|s| material(&board, Color::Black, s)), param(king));
+    }
+
+    #[test]
+    fn test_piece_squares_are_mirrored() {
+        let white = Board::from_fen("4k3/8/8/8/8/2N5/8/4K3 w - - 0 1").unwrap();
+        let black = Board::from_fen("4k3/8/2n5/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let knight_on_c3 = PIECE_SQUARE + PieceKind::Knight as usize * 64 + 18;
+
+        assert_eq!(relative_square(Color::Black, 42), 18);
+        assert_eq!(
+            score(|s| piece_squares(&white, Color::White, s)),
+            score(|s| piece_squares(&black, Color::Black, s))
+        );
+        assert_ne!(param(knight_on_c3), 0);
+    }
+}
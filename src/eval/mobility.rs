@@ -0,0 +1,99 @@
+use crate::bitboard::Bitboard;
+use crate::board::{Board, Color, PieceKind};
+
+use super::FeatureSink;
+use super::king::king_zone;
+use super::params::MOBILITY;
+use super::pawns::{pawn_attacks, pawns_of};
+
+/// Attack units per attacked square in the enemy king zone, by `PieceKind`.
+pub const KING_ATTACK_WEIGHT: [i32; 6] = [0, 2, 2, 3, 5, 0];
+
+/// What one side's pieces reach: mobility, and how hard they press on the
+/// enemy king. The latter feeds the opponent's king safety.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Activity {
+    /// Reachable squares, summed by `PieceKind`. Pawns and kings are not
+    /// counted.
+    pub mobility: [i32; 6],
+    /// Pieces attacking at least one square of the enemy king zone.
+    pub king_attackers: u32,
+    pub king_attack_units: i32,
+}
+
+pub fn evaluate(board: &Board, color: Color) -> Activity {
+    let enemy = color.opposite();
+    // Squares held by our own pieces or guarded by enemy pawns don't count.
+    let area = !(board.occupancy(color) | pawn_attacks(pawns_of(board, enemy), enemy));
+    let zone = board.king(enemy).map_or(Bitboard::default(), king_zone);
+
+    let mut activity = Activity::default();
+
+    for (position, piece) in board.pieces() {
+        if piece.color != color || matches!(piece.kind, PieceKind::Pawn | PieceKind::King) {
+            continue;
+        }
+
+        let kind = piece.kind as usize;
+        let attacks = board.piece_attacks(piece, position.to_index());
+        activity.mobility[kind] += (attacks & area).count() as i32;
+
+        let zone_attacks = (attacks & zone).count() as i32;
+        if zone_attacks > 0 {
+            activity.king_attackers += 1;
+            activity.king_attack_units += KING_ATTACK_WEIGHT[kind] * zone_attacks;
+        }
+    }
+
+    activity
+}
+
+/// Bonus per reachable square, weighted by piece kind.
+pub fn mobility(activity: &Activity, color: Color, sink: &mut impl FeatureSink) {
+    for (kind, &squares) in activity.mobility.iter().enumerate() {
+        if squares != 0 {
+            sink.add(color, MOBILITY + kind, squares);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_start_position_mobility() {
+        let board = Board::new();
+
+        // Only the knights can move: two squares each.
+        for color in [Color::White, Color::Black] {
+            let activity = evaluate(&board, color);
+            assert_eq!(activity.mobility, [0, 4, 0, 0, 0, 0]);
+            assert_eq!(activity.king_attackers, 0);
+        }
+    }
+
+    #[test]
+    fn test_mobility_excludes_squares_guarded_by_pawns() {
+        let free = Board::from_fen("4k3/8/8/8/3N4/8/8/4K3 w - - 0 1").unwrap();
+        let guarded = Board::from_fen("4k3/3p4/8/8/3N4/8/8/4K3 w - - 0 1").unwrap();
+        let knight = PieceKind::Knight as usize;
+
+        assert_eq!(evaluate(&free, Color::White).mobility[knight], 8);
+        assert_eq!(evaluate(&guarded, Color::White).mobility[knight], 6);
+    }
+
+    #[test]
+    fn test_king_attackers() {
+        let board = Board::from_fen("6k1/5ppp/8/6N1/8/3B4/8/1Q2K3 w - - 0 1").unwrap();
+        let activity = evaluate(&board, Color::White);
+
+        // The knight hits f7 and h7, the bishop h7; the queen on b1 is blocked by the bishop.
+        assert_eq!(activity.king_attackers, 2);
+        assert_eq!(
+            activity.king_attack_units,
+            2 * KING_ATTACK_WEIGHT[PieceKind::Knight as usize]
+                + KING_ATTACK_WEIGHT[PieceKind::Bishop as usize]
+        );
+    }
+}
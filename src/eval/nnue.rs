@@ -0,0 +1,481 @@
+//! NNUE evaluation: a (768 -> N) x 2 -> 1 network with one accumulator per
+//! perspective, clipped ReLU activation and integer quantisation.
+//!
+//! Network files are raw little-endian `i16`s in this order: feature
+//! weights (768 x N, feature-major), feature biases (N), output weights
+//! (2 x N, side to move first) and the output bias, optionally zero-padded
+//! to a 64-byte boundary.
+
+use std::fmt::Display;
+use std::path::Path;
+
+use crate::board::{Board, Color, Piece};
+use crate::shared::SquareIndex;
+use crate::zobrist::piece_index;
+
+use super::Evaluate;
+
+pub const INPUTS: usize = 768;
+/// Quantisation of the feature layer: activations are clipped to 0..=QA.
+pub const QA: i32 = 255;
+/// Quantisation of the output weights.
+pub const QB: i32 = 64;
+/// Converts the network output to centipawns.
+pub const SCALE: i32 = 400;
+
+/// The hidden layer is processed in chunks of this many `i16`s.
+const CHUNK: usize = 16;
+const MAX_PADDING: usize = 64;
+
+#[derive(Debug)]
+pub enum NnueError {
+    Io(std::io::Error),
+    /// The file size doesn't match any hidden layer size that is a
+    /// multiple of 16.
+    InvalidSize(usize),
+}
+
+impl Display for NnueError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NnueError::Io(err) => write!(f, "cannot read network: {}", err),
+            NnueError::InvalidSize(len) => write!(f, "invalid network size: {} bytes", len),
+        }
+    }
+}
+
+impl std::error::Error for NnueError {}
+
+impl From<std::io::Error> for NnueError {
+    fn from(err: std::io::Error) -> Self {
+        NnueError::Io(err)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Network {
+    hidden: usize,
+    feature_weights: Vec<i16>,
+    feature_bias: Vec<i16>,
+    output_weights: Vec<i16>,
+    output_bias: i16,
+}
+
+impl Network {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, NnueError> {
+        Network::from_bytes(&std::fs::read(path)?)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, NnueError> {
+        let invalid = || NnueError::InvalidSize(bytes.len());
+
+        let values = bytes.len() / 2;
+        let hidden = values.checked_sub(1).ok_or_else(invalid)? / (INPUTS + 3);
+        let used = 2 * ((INPUTS + 3) * hidden + 1);
+
+        if hidden == 0
+            || !hidden.is_multiple_of(CHUNK)
+            || bytes.len() - used >= MAX_PADDING
+            || bytes[used..].iter().any(|&b| b != 0)
+        {
+            return Err(invalid());
+        }
+
+        let mut values = bytes[..used]
+            .chunks_exact(2)
+            .map(|pair| i16::from_le_bytes([pair[0], pair[1]]));
+        let mut take = |n: usize| values.by_ref().take(n).collect::<Vec<i16>>();
+
+        Ok(Network {
+            hidden,
+            feature_weights: take(INPUTS * hidden),
+            feature_bias: take(hidden),
+            output_weights: take(2 * hidden),
+            output_bias: take(1)[0],
+        })
+    }
+
+    pub fn hidden_size(&self) -> usize {
+        self.hidden
+    }
+
+    fn feature_weights(&self, feature: usize) -> &[i16] {
+        &self.feature_weights[feature * self.hidden..(feature + 1) * self.hidden]
+    }
+
+    /// Score in centipawns for `side_to_move`.
+    pub fn output(&self, accumulator: &Accumulator, side_to_move: Color) -> i32 {
+        let (us, them) = match side_to_move {
+            Color::White => (&accumulator.white, &accumulator.black),
+            Color::Black => (&accumulator.black, &accumulator.white),
+        };
+
+        let (us_weights, them_weights) = self.output_weights.split_at(self.hidden);
+        let sum = simd::crelu_dot(us, us_weights) + simd::crelu_dot(them, them_weights);
+
+        (sum / QA + self.output_bias as i32) * SCALE / (QA * QB)
+    }
+}
+
+/// Index of `piece` on the 64-square `index` as seen from `perspective`:
+/// the board is mirrored vertically for black, so "own" pieces are always
+/// the first 384 inputs.
+pub fn feature_index(perspective: Color, piece: Piece, index: SquareIndex) -> usize {
+    let (piece, index) = match perspective {
+        Color::White => (piece, index),
+        Color::Black => (Piece::new(piece.kind, piece.color.opposite()), index ^ 56),
+    };
+    piece_index(piece) * 64 + index
+}
+
+/// Hidden-layer values for both perspectives. Kept up to date with
+/// `add_piece` / `remove_piece` as pieces move, or rebuilt with `refresh`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Accumulator {
+    pub white: Vec<i16>,
+    pub black: Vec<i16>,
+}
+
+impl Accumulator {
+    pub fn new(network: &Network) -> Self {
+        Accumulator {
+            white: network.feature_bias.clone(),
+            black: network.feature_bias.clone(),
+        }
+    }
+
+    pub fn refresh(&mut self, network: &Network, board: &Board) {
+        self.white.copy_from_slice(&network.feature_bias);
+        self.black.copy_from_slice(&network.feature_bias);
+
+        for (position, piece) in board.pieces() {
+            self.add_piece(network, piece, position.to_64_index());
+        }
+    }
+
+    pub fn add_piece(&mut self, network: &Network, piece: Piece, index: SquareIndex) {
+        simd::add_assign(
+            &mut self.white,
+            network.feature_weights(feature_index(Color::White, piece, index)),
+        );
+        simd::add_assign(
+            &mut self.black,
+            network.feature_weights(feature_index(Color::Black, piece, index)),
+        );
+    }
+
+    pub fn remove_piece(&mut self, network: &Network, piece: Piece, index: SquareIndex) {
+        simd::sub_assign(
+            &mut self.white,
+            network.feature_weights(feature_index(Color::White, piece, index)),
+        );
+        simd::sub_assign(
+            &mut self.black,
+            network.feature_weights(feature_index(Color::Black, piece, index)),
+        );
+    }
+
+    pub fn move_piece(
+        &mut self,
+        network: &Network,
+        piece: Piece,
+        from: SquareIndex,
+        to: SquareIndex,
+    ) {
+        self.remove_piece(network, piece, from);
+        self.add_piece(network, piece, to);
+    }
+}
+
+/// NNUE evaluation backend. The accumulator is rebuilt for every call until
+/// make/unmake exists to drive the incremental updates.
+#[derive(Clone, Debug)]
+pub struct Nnue {
+    network: Network,
+    accumulator: Accumulator,
+}
+
+impl Nnue {
+    pub fn new(network: Network) -> Self {
+        let accumulator = Accumulator::new(&network);
+        Nnue {
+            network,
+            accumulator,
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, NnueError> {
+        Ok(Nnue::new(Network::load(path)?))
+    }
+}
+
+impl Evaluate for Nnue {
+    fn evaluate(&mut self, board: &Board) -> i32 {
+        self.accumulator.refresh(&self.network, board);
+        self.network.output(&self.accumulator, board.turn)
+    }
+}
+
+/// Vector kernels, with AVX2 used when the CPU supports it.
+mod simd {
+    use super::QA;
+
+    pub fn add_assign(acc: &mut [i16], weights: &[i16]) {
+        #[cfg(target_arch = "x86_64")]
+        if is_x86_feature_detected!("avx2") {
+            // SAFETY: AVX2 support was just checked.
+            return unsafe { avx2::add_assign(acc, weights) };
+        }
+        scalar::add_assign(acc, weights)
+    }
+
+    pub fn sub_assign(acc: &mut [i16], weights: &[i16]) {
+        #[cfg(target_arch = "x86_64")]
+        if is_x86_feature_detected!("avx2") {
+            // SAFETY: AVX2 support was just checked.
+            return unsafe { avx2::sub_assign(acc, weights) };
+        }
+        scalar::sub_assign(acc, weights)
+    }
+
+    /// Sum of `clamp(acc, 0, QA) * weights`.
+    pub fn crelu_dot(acc: &[i16], weights: &[i16]) -> i32 {
+        #[cfg(target_arch = "x86_64")]
+        if is_x86_feature_detected!("avx2") {
+            // SAFETY: AVX2 support was just checked.
+            return unsafe { avx2::crelu_dot(acc, weights) };
+        }
+        scalar::crelu_dot(acc, weights)
+    }
+
+    pub mod scalar {
+        use super::QA;
+
+        pub fn add_assign(acc: &mut [i16], weights: &[i16]) {
+            for (a, &w) in acc.iter_mut().zip(weights) {
+                *a = a.wrapping_add(w);
+            }
+        }
+
+        pub fn sub_assign(acc: &mut [i16], weights: &[i16]) {
+            for (a, &w) in acc.iter_mut().zip(weights) {
+                *a = a.wrapping_sub(w);
+            }
+        }
+
+        pub fn crelu_dot(acc: &[i16], weights: &[i16]) -> i32 {
+            acc.iter()
+                .zip(weights)
+                .map(|(&a, &w)| (a as i32).clamp(0, QA) * w as i32)
+                .sum()
+        }
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    pub mod avx2 {
+        use std::arch::x86_64::*;
+
+        use super::super::CHUNK;
+        use super::QA;
+
+        /// # Safety
+        /// The CPU must support AVX2; lengths must be multiples of 16.
+        #[target_feature(enable = "avx2")]
+        pub unsafe fn add_assign(acc: &mut [i16], weights: &[i16]) {
+            for (a, w) in acc.chunks_exact_mut(CHUNK).zip(weights.chunks_exact(CHUNK)) {
+                // SAFETY: both chunks hold exactly 16 i16s (256 bits).
+                unsafe {
+                    let sum = _mm256_add_epi16(
+                        _mm256_loadu_si256(a.as_ptr().cast()),
+                        _mm256_loadu_si256(w.as_ptr().cast()),
+                    );
+                    _mm256_storeu_si256(a.as_mut_ptr().cast(), sum);
+                }
+            }
+        }
+
+        /// # Safety
+        /// The CPU must support AVX2; lengths must be multiples of 16.
+        #[target_feature(enable = "avx2")]
+        pub unsafe fn sub_assign(acc: &mut [i16], weights: &[i16]) {
+            for (a, w) in acc.chunks_exact_mut(CHUNK).zip(weights.chunks_exact(CHUNK)) {
+                // SAFETY: both chunks hold exactly 16 i16s (256 bits).
+                unsafe {
+                    let difference = _mm256_sub_epi16(
+                        _mm256_loadu_si256(a.as_ptr().cast()),
+                        _mm256_loadu_si256(w.as_ptr().cast()),
+                    );
+                    _mm256_storeu_si256(a.as_mut_ptr().cast(), difference);
+                }
+            }
+        }
+
+        /// # Safety
+        /// The CPU must support AVX2; lengths must be multiples of 16.
+        #[target_feature(enable = "avx2")]
+        pub unsafe fn crelu_dot(acc: &[i16], weights: &[i16]) -> i32 {
+            let zero = _mm256_setzero_si256();
+            let max = _mm256_set1_epi16(QA as i16);
+            let mut sum = _mm256_setzero_si256();
+
+            for (a, w) in acc.chunks_exact(CHUNK).zip(weights.chunks_exact(CHUNK)) {
+                // SAFETY: both chunks hold exactly 16 i16s (256 bits).
+                unsafe {
+                    let a = _mm256_loadu_si256(a.as_ptr().cast());
+                    let w = _mm256_loadu_si256(w.as_ptr().cast());
+                    let clipped = _mm256_min_epi16(_mm256_max_epi16(a, zero), max);
+                    sum = _mm256_add_epi32(sum, _mm256_madd_epi16(clipped, w));
+                }
+            }
+
+            let mut lanes = [0i32; 8];
+            // SAFETY: `lanes` is exactly 256 bits.
+            unsafe { _mm256_storeu_si256(lanes.as_mut_ptr().cast(), sum) };
+            lanes.iter().sum()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::PieceKind;
+
+    const HIDDEN: usize = 32;
+
+    /// A small network with deterministic pseudo-random weights.
+    fn test_network_bytes() -> Vec<u8> {
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let mut next = |range: i16| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % (2 * range as u64 + 1)) as i16 - range
+        };
+
+        let count = (INPUTS + 3) * HIDDEN + 1;
+        let mut bytes: Vec<u8> = (0..count)
+            .flat_map(|i| {
+                let range = if i < INPUTS * HIDDEN { 40 } else { 100 };
+                next(range).to_le_bytes()
+            })
+            .collect();
+        bytes.resize(bytes.len().next_multiple_of(64), 0);
+        bytes
+    }
+
+    fn test_network() -> Network {
+        Network::from_bytes(&test_network_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_load_sizes() {
+        let bytes = test_network_bytes();
+
+        assert_eq!(Network::from_bytes(&bytes).unwrap().hidden_size(), HIDDEN);
+        assert!(matches!(
+            Network::from_bytes(&bytes[..bytes.len() - 100]),
+            Err(NnueError::InvalidSize(_))
+        ));
+        assert!(matches!(
+            Network::from_bytes(&[]),
+            Err(NnueError::InvalidSize(0))
+        ));
+        assert!(matches!(
+            Network::load("/nonexistent/andrej.nnue"),
+            Err(NnueError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_feature_index_mirrors_for_black() {
+        let white_pawn = Piece::new(PieceKind::Pawn, Color::White);
+        let black_pawn = Piece::new(PieceKind::Pawn, Color::Black);
+
+        // e2 for white looks like e7 for black from the other side.
+        assert_eq!(
+            feature_index(Color::White, white_pawn, 12),
+            feature_index(Color::Black, black_pawn, 52)
+        );
+        assert_eq!(feature_index(Color::White, white_pawn, 0), 0);
+        assert_eq!(feature_index(Color::White, black_pawn, 0), 6 * 64);
+    }
+
+    #[test]
+    fn test_incremental_updates_match_refresh() {
+        let network = test_network();
+        let before = Board::new();
+        let after =
+            Board::from_fen("rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2")
+                .unwrap();
+
+        let mut incremental = Accumulator::new(&network);
+        incremental.refresh(&network, &before);
+
+        let white_pawn = Piece::new(PieceKind::Pawn, Color::White);
+        let black_pawn = Piece::new(PieceKind::Pawn, Color::Black);
+        let knight = Piece::new(PieceKind::Knight, Color::White);
+        incremental.move_piece(&network, white_pawn, 12, 28);
+        incremental.move_piece(&network, black_pawn, 52, 36);
+        incremental.move_piece(&network, knight, 6, 21);
+
+        let mut refreshed = Accumulator::new(&network);
+        refreshed.refresh(&network, &after);
+
+        assert_eq!(incremental, refreshed);
+
+        incremental.move_piece(&network, knight, 21, 6);
+        incremental.move_piece(&network, black_pawn, 36, 52);
+        incremental.move_piece(&network, white_pawn, 28, 12);
+        refreshed.refresh(&network, &before);
+
+        assert_eq!(incremental, refreshed);
+    }
+
+    #[test]
+    fn test_simd_matches_scalar() {
+        let acc: Vec<i16> = (0..64).map(|i| (i * 37 % 600) as i16 - 200).collect();
+        let weights: Vec<i16> = (0..64).map(|i| (i * 53 % 201) as i16 - 100).collect();
+
+        let mut added = acc.clone();
+        simd::add_assign(&mut added, &weights);
+        let mut expected = acc.clone();
+        simd::scalar::add_assign(&mut expected, &weights);
+        assert_eq!(added, expected);
+
+        simd::sub_assign(&mut added, &weights);
+        assert_eq!(added, acc);
+
+        assert_eq!(
+            simd::crelu_dot(&acc, &weights),
+            simd::scalar::crelu_dot(&acc, &weights)
+        );
+    }
+
+    #[test]
+    fn test_evaluation_is_symmetric() {
+        let mut nnue = Nnue::new(test_network());
+        let white =
+            Board::from_fen("r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4")
+                .unwrap();
+        let black =
+            Board::from_fen("rnbqk2r/pppp1ppp/5n2/2b1p3/4P3/2N2N2/PPPP1PPP/R1BQKB1R b KQkq - 4 4")
+                .unwrap();
+
+        assert_eq!(nnue.evaluate(&white), nnue.evaluate(&black));
+    }
+
+    #[test]
+    fn test_evaluators_are_interchangeable() {
+        let board = Board::new();
+        let mut evaluators: Vec<Box<dyn Evaluate>> = vec![
+            Box::new(super::super::Evaluator::new()),
+            Box::new(Nnue::new(test_network())),
+        ];
+
+        for evaluator in evaluators.iter_mut() {
+            let score = evaluator.evaluate(&board);
+            assert_eq!(score, evaluator.evaluate(&board));
+        }
+    }
+}
@@ -0,0 +1,341 @@
+//! Tunable evaluation weights, stored as one flat vector so the tuner can
+//! treat them uniformly. The constants below are offsets into it; array
+//! parameters occupy consecutive slots.
+//!
+//! Weights are saved as a flat TOML table, one key per parameter:
+//!
+//! ```toml
+//! material = [100, 320, 330, 500, 900, 0]
+//! doubled_pawn = -12
+//! passed_pawn = [0, 5, 10, 20, 35, 60, 100, 0]
+//! ```
+
+use std::fmt::Display;
+
+/// By `PieceKind`.
+pub const MATERIAL: usize = 0;
+/// By `PieceKind`, then by square seen from the piece owner's side: a1 is
+/// 0 for white and a8 is 0 for black.
+pub const PIECE_SQUARE: usize = MATERIAL + 6;
+pub const DOUBLED_PAWN: usize = PIECE_SQUARE + 6 * 64;
+pub const ISOLATED_PAWN: usize = DOUBLED_PAWN + 1;
+pub const BACKWARD_PAWN: usize = ISOLATED_PAWN + 1;
+/// By rank from the pawn owner's side (0 = first rank).
+pub const CONNECTED_PAWN: usize = BACKWARD_PAWN + 1;
+/// By relative rank.
+pub const PASSED_PAWN: usize = CONNECTED_PAWN + 8;
+/// By relative rank, when the square in front of the passer is occupied.
+pub const BLOCKED_PASSED_PAWN: usize = PASSED_PAWN + 8;
+/// Per reachable square, by `PieceKind`.
+pub const MOBILITY: usize = BLOCKED_PASSED_PAWN + 8;
+/// Penalty by king attack units (see `mobility::Activity`).
+pub const KING_SAFETY: usize = MOBILITY + 6;
+/// Shield pawn one and two ranks in front of the king.
+pub const PAWN_SHIELD: usize = KING_SAFETY + 100;
+pub const KING_OPEN_FILE: usize = PAWN_SHIELD + 2;
+pub const KING_SEMI_OPEN_FILE: usize = KING_OPEN_FILE + 1;
+pub const BISHOP_PAIR: usize = KING_SEMI_OPEN_FILE + 1;
+pub const ROOK_OPEN_FILE: usize = BISHOP_PAIR + 1;
+pub const ROOK_SEMI_OPEN_FILE: usize = ROOK_OPEN_FILE + 1;
+pub const ROOK_ON_SEVENTH: usize = ROOK_SEMI_OPEN_FILE + 1;
+pub const PARAM_COUNT: usize = ROOK_ON_SEVENTH + 1;
+
+/// Name, offset and length of every parameter, in file order.
+pub const LAYOUT: [(&str, usize, usize); 17] = [
+    ("material", MATERIAL, 6),
+    ("piece_square", PIECE_SQUARE, 6 * 64),
+    ("doubled_pawn", DOUBLED_PAWN, 1),
+    ("isolated_pawn", ISOLATED_PAWN, 1),
+    ("backward_pawn", BACKWARD_PAWN, 1),
+    ("connected_pawn", CONNECTED_PAWN, 8),
+    ("passed_pawn", PASSED_PAWN, 8),
+    ("blocked_passed_pawn", BLOCKED_PASSED_PAWN, 8),
+    ("mobility", MOBILITY, 6),
+    ("king_safety", KING_SAFETY, 100),
+    ("pawn_shield", PAWN_SHIELD, 2),
+    ("king_open_file", KING_OPEN_FILE, 1),
+    ("king_semi_open_file", KING_SEMI_OPEN_FILE, 1),
+    ("bishop_pair", BISHOP_PAIR, 1),
+    ("rook_open_file", ROOK_OPEN_FILE, 1),
+    ("rook_semi_open_file", ROOK_SEMI_OPEN_FILE, 1),
+    ("rook_on_seventh", ROOK_ON_SEVENTH, 1),
+];
+
+const DEFAULTS: [(usize, &[i32]); 22] = [
+    (MATERIAL, &[100, 320, 330, 500, 900, 0]),
+    (PIECE_SQUARE, &PAWN_SQUARES),
+    (PIECE_SQUARE + 64, &KNIGHT_SQUARES),
+    (PIECE_SQUARE + 2 * 64, &BISHOP_SQUARES),
+    (PIECE_SQUARE + 3 * 64, &ROOK_SQUARES),
+    (PIECE_SQUARE + 4 * 64, &QUEEN_SQUARES),
+    (PIECE_SQUARE + 5 * 64, &KING_SQUARES),
+    (DOUBLED_PAWN, &[-12]),
+    (ISOLATED_PAWN, &[-10]),
+    (BACKWARD_PAWN, &[-8]),
+    (CONNECTED_PAWN, &[0, 0, 4, 6, 10, 18, 30, 0]),
+    (PASSED_PAWN, &[0, 5, 10, 20, 35, 60, 100, 0]),
+    (BLOCKED_PASSED_PAWN, &[0, 2, 5, 10, 18, 30, 50, 0]),
+    (MOBILITY, &[0, 4, 5, 2, 1, 0]),
+    (
+        KING_SAFETY,
+        &[
+            0, 0, 1, 2, 3, 5, 7, 9, 12, 15, 18, 22, 26, 30, 35, 39, 44, 50, 56, 62, 68, 75, 82, 85,
+            89, 97, 105, 113, 122, 131, 140, 150, 169, 180, 191, 202, 213, 225, 237, 248, 260, 272,
+            283, 295, 307, 319, 330, 342, 354, 366, 377, 389, 401, 412, 424, 436, 448, 459, 471,
+            483, 494, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500,
+            500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500,
+            500, 500, 500, 500, 500, 500, 500,
+        ],
+    ),
+    (PAWN_SHIELD, &[10, 5]),
+    (KING_OPEN_FILE, &[-20]),
+    (KING_SEMI_OPEN_FILE, &[-10]),
+    (BISHOP_PAIR, &[30]),
+    (ROOK_OPEN_FILE, &[20]),
+    (ROOK_SEMI_OPEN_FILE, &[10]),
+    (ROOK_ON_SEVENTH, &[20]),
+];
+
+// Piece-square defaults from white's side, rank 1 first.
+
+#[rustfmt::skip]
+const PAWN_SQUARES: [i32; 64] = [
+      0,   0,   0,   0,   0,   0,   0,   0,
+      5,  10,  10, -20, -20,  10,  10,   5,
+      5,  -5, -10,   0,   0, -10,  -5,   5,
+      0,   0,   0,  20,  20,   0,   0,   0,
+      5,   5,  10,  25,  25,  10,   5,   5,
+     10,  10,  20,  30,  30,  20,  10,  10,
+     50,  50,  50,  50,  50,  50,  50,  50,
+      0,   0,   0,   0,   0,   0,   0,   0,
+];
+
+#[rustfmt::skip]
+const KNIGHT_SQUARES: [i32; 64] = [
+    -50, -40, -30, -30, -30, -30, -40, -50,
+    -40, -20,   0,   5,   5,   0, -20, -40,
+    -30,   5,  10,  15,  15,  10,   5, -30,
+    -30,   0,  15,  20,  20,  15,   0, -30,
+    -30,   5,  15,  20,  20,  15,   5, -30,
+    -30,   0,  10,  15,  15,  10,   0, -30,
+    -40, -20,   0,   0,   0,   0, -20, -40,
+    -50, -40, -30, -30, -30, -30, -40, -50,
+];
+
+#[rustfmt::skip]
+const BISHOP_SQUARES: [i32; 64] = [
+    -20, -10, -10, -10, -10, -10, -10, -20,
+    -10,   5,   0,   0,   0,   0,   5, -10,
+    -10,  10,  10,  10,  10,  10,  10, -10,
+    -10,   0,  10,  10,  10,  10,   0, -10,
+    -10,   5,   5,  10,  10,   5,   5, -10,
+    -10,   0,   5,  10,  10,   5,   0, -10,
+    -10,   0,   0,   0,   0,   0,   0, -10,
+    -20, -10, -10, -10, -10, -10, -10, -20,
+];
+
+#[rustfmt::skip]
+const ROOK_SQUARES: [i32; 64] = [
+      0,   0,   0,   5,   5,   0,   0,   0,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+      5,  10,  10,  10,  10,  10,  10,   5,
+      0,   0,   0,   0,   0,   0,   0,   0,
+];
+
+#[rustfmt::skip]
+const QUEEN_SQUARES: [i32; 64] = [
+    -20, -10, -10,  -5,  -5, -10, -10, -20,
+    -10,   0,   0,   0,   0,   0,   0, -10,
+    -10,   0,   5,   5,   5,   5,   0, -10,
+     -5,   0,   5,   5,   5,   5,   0,  -5,
+     -5,   0,   5,   5,   5,   5,   0,  -5,
+    -10,   0,   5,   5,   5,   5,   0, -10,
+    -10,   0,   0,   0,   0,   0,   0, -10,
+    -20, -10, -10,  -5,  -5, -10, -10, -20,
+];
+
+#[rustfmt::skip]
+const KING_SQUARES: [i32; 64] = [
+     20,  30,  10,   0,   0,  10,  30,  20,
+     20,  20,   0,   0,   0,   0,  20,  20,
+    -10, -20, -20, -20, -20, -20, -20, -10,
+    -20, -30, -30, -40, -40, -30, -30, -20,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParamsError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl Display for ParamsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParamsError {}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EvalParams(pub [i32; PARAM_COUNT]);
+
+impl Default for EvalParams {
+    fn default() -> Self {
+        let mut params = [0; PARAM_COUNT];
+        for (offset, values) in DEFAULTS {
+            params[offset..offset + values.len()].copy_from_slice(values);
+        }
+        EvalParams(params)
+    }
+}
+
+impl std::ops::Index<usize> for EvalParams {
+    type Output = i32;
+
+    fn index(&self, index: usize) -> &i32 {
+        &self.0[index]
+    }
+}
+
+impl EvalParams {
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(EvalParams::from_toml(&std::fs::read_to_string(path)?)?)
+    }
+
+    /// Parses the flat TOML format written by `to_toml`. Parameters missing
+    /// from the input keep their default values.
+    pub fn from_toml(input: &str) -> Result<Self, ParamsError> {
+        let mut params = EvalParams::default();
+        let mut lines = input.lines().enumerate();
+
+        while let Some((index, line)) = lines.next() {
+            let error = |message: String| ParamsError {
+                line: index + 1,
+                message,
+            };
+
+            let line = strip_comment(line);
+            if line.is_empty() {
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| error("expected `key = value`".to_string()))?;
+            let key = key.trim();
+
+            // Arrays may be split over several lines.
+            let mut value = value.trim().to_string();
+            if value.starts_with('[') {
+                while !value.ends_with(']') {
+                    let (_, next) = lines
+                        .next()
+                        .ok_or_else(|| error(format!("unterminated array for `{}`", key)))?;
+                    value.push_str(strip_comment(next));
+                }
+                value = value[1..value.len() - 1].to_string();
+            }
+
+            let values = value
+                .split(',')
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(|v| {
+                    v.parse::<i32>()
+                        .map_err(|_| error(format!("invalid integer `{}`", v)))
+                })
+                .collect::<Result<Vec<i32>, ParamsError>>()?;
+
+            let &(_, offset, len) = LAYOUT
+                .iter()
+                .find(|(name, _, _)| *name == key)
+                .ok_or_else(|| error(format!("unknown parameter `{}`", key)))?;
+
+            if values.len() != len {
+                return Err(error(format!(
+                    "`{}` needs {} values, got {}",
+                    key,
+                    len,
+                    values.len()
+                )));
+            }
+            params.0[offset..offset + len].copy_from_slice(&values);
+        }
+
+        Ok(params)
+    }
+
+    pub fn to_toml(&self) -> String {
+        let mut output = String::new();
+
+        for (name, offset, len) in LAYOUT {
+            let values = &self.0[offset..offset + len];
+            if len == 1 {
+                output.push_str(&format!("{} = {}\n", name, values[0]));
+            } else {
+                let values: Vec<String> = values.iter().map(i32::to_string).collect();
+                output.push_str(&format!("{} = [{}]\n", name, values.join(", ")));
+            }
+        }
+
+        output
+    }
+}
+
+fn strip_comment(line: &str) -> &str {
+    line.split('#').next().unwrap_or("").trim()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_covers_every_slot() {
+        let mut offset = 0;
+        for (_, start, len) in LAYOUT {
+            assert_eq!(start, offset);
+            offset += len;
+        }
+        assert_eq!(offset, PARAM_COUNT);
+    }
+
+    #[test]
+    fn test_toml_round_trip() {
+        let mut params = EvalParams::default();
+        params.0[PASSED_PAWN + 6] = 123;
+        params.0[BISHOP_PAIR] = -4;
+
+        assert_eq!(EvalParams::from_toml(&params.to_toml()).unwrap(), params);
+    }
+
+    #[test]
+    fn test_partial_toml_keeps_defaults() {
+        let params = EvalParams::from_toml(
+            "# tuned\nbishop_pair = 42 # comment\npawn_shield = [\n  12,\n  6,\n]\n",
+        )
+        .unwrap();
+
+        assert_eq!(params[BISHOP_PAIR], 42);
+        assert_eq!(params[PAWN_SHIELD], 12);
+        assert_eq!(params[PAWN_SHIELD + 1], 6);
+        assert_eq!(params[DOUBLED_PAWN], EvalParams::default()[DOUBLED_PAWN]);
+    }
+
+    #[test]
+    fn test_toml_errors() {
+        assert_eq!(EvalParams::from_toml("queen = 900").unwrap_err().line, 1);
+        assert!(EvalParams::from_toml("\npawn_shield = [1]").is_err());
+        assert!(EvalParams::from_toml("bishop_pair = x").is_err());
+        assert!(EvalParams::from_toml("bishop_pair").is_err());
+        assert!(EvalParams::from_toml("pawn_shield = [1,").is_err());
+    }
+}
@@ -0,0 +1,407 @@
+use crate::bitboard::{ADJACENT_FILE_MASKS, Bitboard, EMPTY, FILE_MASKS, RANK_MASKS};
+use crate::board::{Board, Color, ColoredPair, Position};
+use crate::shared::SquareIndex;
+
+use super::FeatureSink;
+use super::params::{
+    BACKWARD_PAWN, BLOCKED_PASSED_PAWN, CONNECTED_PAWN, DOUBLED_PAWN, ISOLATED_PAWN, PASSED_PAWN,
+};
+
+const DEFAULT_TABLE_ENTRIES: usize = 1 << 14;
+
+const fn color_index(color: Color) -> usize {
+    match color {
+        Color::White => 0,
+        Color::Black => 1,
+    }
+}
+
+/// Squares strictly in front of a square on its own file.
+const FORWARD_MASKS: [[Bitboard; 64]; 2] = {
+    let mut masks = [[EMPTY; 64]; 2];
+    let mut square = 0;
+    while square < 64 {
+        let file = FILE_MASKS[square % 8].0;
+        masks[0][square] = Bitboard(file & (u64::MAX << square << 1));
+        masks[1][square] = Bitboard(file & !(u64::MAX << square));
+        square += 1;
+    }
+    masks
+};
+
+/// Squares in front of a square on its own and adjacent files: a pawn is
+/// passed when no enemy pawn stands on these.
+const PASSED_MASKS: [[Bitboard; 64]; 2] = {
+    let mut masks = [[EMPTY; 64]; 2];
+    let mut side = 0;
+    while side < 2 {
+        let mut square = 0;
+        while square < 64 {
+            let forward = FORWARD_MASKS[side][square].0;
+            let file = square % 8;
+            let mut mask = forward;
+            if file > 0 {
+                mask |= forward >> 1;
+            }
+            if file < 7 {
+                mask |= forward << 1;
+            }
+            masks[side][square] = Bitboard(mask);
+            square += 1;
+        }
+        side += 1;
+    }
+    masks
+};
+
+/// Squares on adjacent files at the same rank or behind: the friendly pawns
+/// that support a pawn now or could advance to do so.
+const SUPPORT_MASKS: [[Bitboard; 64]; 2] = {
+    let mut masks = [[EMPTY; 64]; 2];
+    let mut square = 0;
+    while square < 64 {
+        let adjacent = ADJACENT_FILE_MASKS[square % 8].0;
+        let rank = square / 8;
+        masks[0][square] = Bitboard(adjacent & !(u64::MAX << (rank * 8) << 8));
+        masks[1][square] = Bitboard(adjacent & (u64::MAX << (rank * 8)));
+        square += 1;
+    }
+    masks
+};
+
+pub fn forward_mask(color: Color, square: SquareIndex) -> Bitboard {
+    FORWARD_MASKS[color_index(color)][square]
+}
+
+pub fn passed_mask(color: Color, square: SquareIndex) -> Bitboard {
+    PASSED_MASKS[color_index(color)][square]
+}
+
+pub fn relative_rank(color: Color, square: SquareIndex) -> usize {
+    match color {
+        Color::White => square / 8,
+        Color::Black => 7 - square / 8,
+    }
+}
+
+/// Squares attacked by `pawns` of the given colour.
+pub fn pawn_attacks(pawns: Bitboard, color: Color) -> Bitboard {
+    let not_a = !FILE_MASKS[0].0;
+    let not_h = !FILE_MASKS[7].0;
+
+    match color {
+        Color::White => Bitboard(((pawns.0 & not_a) << 7) | ((pawns.0 & not_h) << 9)),
+        Color::Black => Bitboard(((pawns.0 & not_a) >> 9) | ((pawns.0 & not_h) >> 7)),
+    }
+}
+
+pub fn pawns_of(board: &Board, color: Color) -> Bitboard {
+    match color {
+        Color::White => board.pawns.white,
+        Color::Black => board.pawns.black,
+    }
+}
+
+/// The square in front of a pawn, or `None` for a pawn already on the
+/// last rank, which `from_fen` accepts.
+fn stop_square(color: Color, square: SquareIndex) -> Option<SquareIndex> {
+    match color {
+        Color::White => (square < 56).then(|| square + 8),
+        Color::Black => square.checked_sub(8),
+    }
+}
+
+/// One side's pawns, classified by structural feature. A pawn can be in
+/// several sets at once (e.g. isolated and passed).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct PawnFeatures {
+    /// Pawns with a friendly pawn in front of them on the same file.
+    pub doubled: Bitboard,
+    pub isolated: Bitboard,
+    /// Pawns that can no longer be supported by a neighbour and whose
+    /// stop square is controlled by an enemy pawn.
+    pub backward: Bitboard,
+    /// Pawns defended by a pawn or standing next to one.
+    pub connected: Bitboard,
+    /// The front-most pawn on a file with no enemy pawns ahead of it on
+    /// its own or the adjacent files.
+    pub passed: Bitboard,
+}
+
+impl PawnFeatures {
+    pub fn of(board: &Board, color: Color) -> Self {
+        let own = pawns_of(board, color);
+        let enemy = pawns_of(board, color.opposite());
+        let own_attacks = pawn_attacks(own, color);
+        let enemy_attacks = pawn_attacks(enemy, color.opposite());
+        let side = color_index(color);
+
+        let mut features = PawnFeatures::default();
+
+        for square in own.iter() {
+            let file = square % 8;
+            let rank = square / 8;
+
+            let doubled = !(FORWARD_MASKS[side][square] & own).is_empty();
+            let isolated = (ADJACENT_FILE_MASKS[file] & own).is_empty();
+            let phalanx = !(ADJACENT_FILE_MASKS[file] & RANK_MASKS[rank] & own).is_empty();
+            let stop = stop_square(color, square);
+
+            if doubled {
+                features.doubled.set(square);
+            }
+            if isolated {
+                features.isolated.set(square);
+            } else if (SUPPORT_MASKS[side][square] & own).is_empty()
+                && stop.is_some_and(|stop| enemy_attacks.is_set(stop))
+            {
+                features.backward.set(square);
+            }
+            if phalanx || own_attacks.is_set(square) {
+                features.connected.set(square);
+            }
+            if !doubled && (PASSED_MASKS[side][square] & enemy).is_empty() {
+                features.passed.set(square);
+            }
+        }
+
+        features
+    }
+
+    /// Records everything that depends on pawns alone.
+    pub fn record(&self, color: Color, sink: &mut impl FeatureSink) {
+        sink.add(color, DOUBLED_PAWN, self.doubled.count() as i32);
+        sink.add(color, ISOLATED_PAWN, self.isolated.count() as i32);
+        sink.add(color, BACKWARD_PAWN, self.backward.count() as i32);
+
+        for square in self.connected.iter() {
+            sink.add(color, CONNECTED_PAWN + relative_rank(color, square), 1);
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+struct PawnEntry {
+    key: u64,
+    features: ColoredPair<PawnFeatures>,
+}
+
+/// Cache of pawn structure features keyed by `Board::pawn_key`. Pawn
+/// structures change rarely during a search, so most probes hit. Features
+/// rather than scores are stored, so entries stay valid when the
+/// evaluation weights change.
+#[derive(Debug)]
+pub struct PawnTable {
+    entries: Vec<PawnEntry>,
+}
+
+impl PawnTable {
+    /// `entries` is rounded up to a power of two.
+    pub fn new(entries: usize) -> Self {
+        PawnTable {
+            entries: vec![PawnEntry::default(); entries.max(1).next_power_of_two()],
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.fill(PawnEntry::default());
+    }
+
+    /// Returns both sides' pawn features, computing and storing them on a
+    /// miss. An empty default entry has key 0, which is also the correct
+    /// result for a board without pawns.
+    pub fn probe(&mut self, board: &Board) -> ColoredPair<PawnFeatures> {
+        let key = board.pawn_key.0;
+        let index = key as usize & (self.entries.len() - 1);
+
+        if self.entries[index].key != key {
+            self.entries[index] = PawnEntry {
+                key,
+                features: ColoredPair::from_fn(|color| PawnFeatures::of(board, color)),
+            };
+        }
+
+        self.entries[index].features
+    }
+}
+
+impl Default for PawnTable {
+    fn default() -> Self {
+        PawnTable::new(DEFAULT_TABLE_ENTRIES)
+    }
+}
+
+/// Passed pawns of one side, by rank and whether they are blocked. Not
+/// cached, since blockers can be any piece.
+pub fn passed_pawns(board: &Board, passed: Bitboard, color: Color, sink: &mut impl FeatureSink) {
+    for square in passed.iter() {
+        let rank = relative_rank(color, square);
+        let blocked = stop_square(color, square)
+            .is_some_and(|stop| board.piece_at(Position::from_64_index(stop)).is_some());
+
+        if blocked {
+            sink.add(color, BLOCKED_PASSED_PAWN + rank, 1);
+        } else {
+            sink.add(color, PASSED_PAWN + rank, 1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+    use crate::eval::Scorer;
+    use crate::eval::params::EvalParams;
+    use crate::eval::test_helpers::param;
+
+    /// Pawn structure and passed pawn score from white's point of view.
+    fn evaluate(board: &Board, table: &mut PawnTable) -> i32 {
+        let params = EvalParams::default();
+        let mut scorer = Scorer::new(&params);
+        let features = table.probe(board);
+
+        for color in [Color::White, Color::Black] {
+            let features = features.get(color);
+            features.record(color, &mut scorer);
+            passed_pawns(board, features.passed, color, &mut scorer);
+        }
+
+        scorer.total()
+    }
+
+    fn squares(names: &[&str]) -> Bitboard {
+        let mut bb = Bitboard::default();
+        for name in names {
+            bb.set(name.parse::<Position>().unwrap().to_64_index());
+        }
+        bb
+    }
+
+    fn evaluate_fen(fen: &str) -> i32 {
+        evaluate(&Board::from_fen(fen).unwrap(), &mut PawnTable::new(16))
+    }
+
+    #[test]
+    fn test_start_position_is_balanced() {
+        let board = Board::new();
+        let white = PawnFeatures::of(&board, Color::White);
+
+        assert_eq!(board.pawns.white.count(), 8);
+        assert_eq!(board.pawns.black.count(), 8);
+        assert_eq!(board.pawns.both.count(), 16);
+        assert!(white.passed.is_empty());
+        assert!(white.isolated.is_empty());
+        assert_eq!(white.connected, board.pawns.white);
+        assert_eq!(evaluate(&board, &mut PawnTable::default()), 0);
+    }
+
+    #[test]
+    fn test_masks() {
+        assert_eq!(
+            forward_mask(Color::White, squares(&["e4"]).pop()),
+            squares(&["e5", "e6", "e7", "e8"])
+        );
+        assert_eq!(
+            forward_mask(Color::Black, squares(&["e4"]).pop()),
+            squares(&["e3", "e2", "e1"])
+        );
+        assert_eq!(
+            passed_mask(Color::White, squares(&["a6"]).pop()),
+            squares(&["a7", "a8", "b7", "b8"])
+        );
+        assert_eq!(
+            pawn_attacks(squares(&["a2", "h2"]), Color::White),
+            squares(&["b3", "g3"])
+        );
+        assert_eq!(
+            pawn_attacks(squares(&["a7", "e7"]), Color::Black),
+            squares(&["b6", "d6", "f6"])
+        );
+    }
+
+    #[test]
+    fn test_doubled_and_isolated() {
+        let board = Board::from_fen("4k3/8/8/8/8/P7/P7/4K3 w - - 0 1").unwrap();
+        let white = PawnFeatures::of(&board, Color::White);
+
+        assert_eq!(white.doubled, squares(&["a2"]));
+        assert_eq!(white.isolated, squares(&["a2", "a3"]));
+        assert_eq!(white.passed, squares(&["a3"]));
+        assert_eq!(
+            evaluate(&board, &mut PawnTable::new(16)),
+            param(DOUBLED_PAWN) + 2 * param(ISOLATED_PAWN) + param(PASSED_PAWN + 2)
+        );
+    }
+
+    #[test]
+    fn test_backward_and_connected() {
+        let board = Board::from_fen("4k3/8/8/4p3/2P1P3/3P4/8/4K3 w - - 0 1").unwrap();
+        let white = PawnFeatures::of(&board, Color::White);
+
+        assert_eq!(white.backward, squares(&["d3"]));
+        assert_eq!(white.connected, squares(&["c4", "e4"]));
+        assert!(white.isolated.is_empty());
+    }
+
+    #[test]
+    fn test_passed_pawn_blocker() {
+        let free = evaluate_fen("4k3/8/8/3P4/8/8/8/4K3 w - - 0 1");
+        let blocked = evaluate_fen("4k3/8/3n4/3P4/8/8/8/4K3 w - - 0 1");
+
+        assert_eq!(free, param(ISOLATED_PAWN) + param(PASSED_PAWN + 4));
+        assert_eq!(
+            blocked,
+            param(ISOLATED_PAWN) + param(BLOCKED_PASSED_PAWN + 4)
+        );
+    }
+
+    #[test]
+    fn test_pawns_on_last_rank() {
+        // A white pawn on the eighth rank and a black one on the first have
+        // no stop square, so they can be neither backward nor blocked.
+        for fen in [
+            "4k3/8/8/8/8/8/8/p3K3 w - - 0 1",
+            "P3k3/8/8/8/8/8/8/4K3 w - - 0 1",
+        ] {
+            let board = Board::from_fen(fen).unwrap();
+            for color in [Color::White, Color::Black] {
+                assert!(PawnFeatures::of(&board, color).backward.is_empty());
+            }
+            evaluate_fen(fen);
+        }
+        assert_eq!(
+            evaluate_fen("P3k3/8/8/8/8/8/8/4K3 w - - 0 1"),
+            -evaluate_fen("4k3/8/8/8/8/8/8/p3K3 b - - 0 1")
+        );
+    }
+
+    #[test]
+    fn test_evaluation_is_symmetric() {
+        let white = evaluate_fen("4k3/p7/8/3P4/1P6/2P5/P4PP1/4K3 w - - 0 1");
+        let black = evaluate_fen("4k3/p4pp1/2p5/1p6/3p4/8/P7/4K3 b - - 0 1");
+
+        assert_ne!(white, 0);
+        assert_eq!(white, -black);
+    }
+
+    #[test]
+    fn test_pawn_table_caches_by_pawn_key() {
+        let mut table = PawnTable::new(16);
+        let board = Board::from_fen("4k3/8/8/3P4/8/8/8/4K3 w - - 0 1").unwrap();
+        let same_pawns = Board::from_fen("4k3/8/3n4/3P4/8/8/8/3K4 b - - 0 1").unwrap();
+        let other_pawns = Board::from_fen("4k3/8/8/4P3/8/8/8/4K3 w - - 0 1").unwrap();
+
+        assert_eq!(board.pawn_key, same_pawns.pawn_key);
+        assert_ne!(board.pawn_key, other_pawns.pawn_key);
+
+        let features = table.probe(&board);
+        let index = board.pawn_key.0 as usize & (table.entries.len() - 1);
+        assert_eq!(table.entries[index].key, board.pawn_key.0);
+        assert_eq!(table.probe(&same_pawns).white, features.white);
+        assert_eq!(
+            table.probe(&other_pawns).white,
+            PawnFeatures::of(&other_pawns, Color::White)
+        );
+    }
+}
@@ -0,0 +1,108 @@
+use crate::bitboard::FILE_MASKS;
+use crate::board::{Board, Color, PieceKind};
+
+use super::FeatureSink;
+use super::params::{BISHOP_PAIR, ROOK_ON_SEVENTH, ROOK_OPEN_FILE, ROOK_SEMI_OPEN_FILE};
+use super::pawns::{pawns_of, relative_rank};
+
+fn count(board: &Board, color: Color, kind: PieceKind) -> usize {
+    board
+        .pieces()
+        .filter(|(_, piece)| piece.color == color && piece.kind == kind)
+        .count()
+}
+
+pub fn bishop_pair(board: &Board, color: Color, sink: &mut impl FeatureSink) {
+    if count(board, color, PieceKind::Bishop) >= 2 {
+        sink.add(color, BISHOP_PAIR, 1);
+    }
+}
+
+/// Bonus for rooks on files without pawns, or without friendly pawns.
+pub fn rook_files(board: &Board, color: Color, sink: &mut impl FeatureSink) {
+    let own = pawns_of(board, color);
+    let enemy = pawns_of(board, color.opposite());
+
+    for (position, piece) in board.pieces() {
+        if piece.color != color || piece.kind != PieceKind::Rook {
+            continue;
+        }
+
+        let file = FILE_MASKS[position.file as usize];
+        if !(own & file).is_empty() {
+            continue;
+        }
+        if (enemy & file).is_empty() {
+            sink.add(color, ROOK_OPEN_FILE, 1);
+        } else {
+            sink.add(color, ROOK_SEMI_OPEN_FILE, 1);
+        }
+    }
+}
+
+/// Bonus for rooks on the seventh rank while the enemy king is stuck on
+/// the eighth or enemy pawns are still on the seventh.
+pub fn rook_on_seventh(board: &Board, color: Color, sink: &mut impl FeatureSink) {
+    let enemy = color.opposite();
+    let seventh = |index| relative_rank(color, index) == 6;
+
+    let king_cut_off = board
+        .king(enemy)
+        .is_some_and(|king| relative_rank(color, king.to_64_index()) == 7);
+    let pawns_on_seventh = pawns_of(board, enemy).iter().any(seventh);
+    if !king_cut_off && !pawns_on_seventh {
+        return;
+    }
+
+    let rooks = board
+        .pieces()
+        .filter(|(position, piece)| {
+            piece.color == color && piece.kind == PieceKind::Rook && seventh(position.to_64_index())
+        })
+        .count() as i32;
+    if rooks > 0 {
+        sink.add(color, ROOK_ON_SEVENTH, rooks);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::test_helpers::{param, score};
+
+    #[test]
+    fn test_bishop_pair() {
+        let board = Board::from_fen("2b1k3/8/8/8/8/8/8/2B1KB2 w - - 0 1").unwrap();
+
+        assert_eq!(
+            score(|s| bishop_pair(&board, Color::White, s)),
+            param(BISHOP_PAIR)
+        );
+        assert_eq!(score(|s| bishop_pair(&board, Color::Black, s)), 0);
+    }
+
+    #[test]
+    fn test_rook_files() {
+        let board = Board::from_fen("4k3/p7/8/8/8/8/1P6/R1R1K2R w - - 0 1").unwrap();
+
+        // a: half-open, c: open, h: open; the b-file has no rook.
+        assert_eq!(
+            score(|s| rook_files(&board, Color::White, s)),
+            param(ROOK_SEMI_OPEN_FILE) + 2 * param(ROOK_OPEN_FILE)
+        );
+    }
+
+    #[test]
+    fn test_rook_on_seventh() {
+        let cut_off = Board::from_fen("6k1/R7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let king_out = Board::from_fen("8/R7/6k1/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let pawns = Board::from_fen("8/R6p/6k1/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let black = Board::from_fen("4k3/8/8/8/8/8/r7/6K1 b - - 0 1").unwrap();
+        let bonus = param(ROOK_ON_SEVENTH);
+
+        assert_eq!(score(|s| rook_on_seventh(&cut_off, Color::White, s)), bonus);
+        assert_eq!(score(|s| rook_on_seventh(&king_out, Color::White, s)), 0);
+        assert_eq!(score(|s| rook_on_seventh(&pawns, Color::White, s)), bonus);
+        assert_eq!(score(|s| rook_on_seventh(&black, Color::Black, s)), bonus);
+    }
+}
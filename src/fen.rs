@@ -0,0 +1,843 @@
+use std::fmt::Display;
+
+use crate::board::{
+    Board, CastlingRights, CastlingSide, Color, ColoredPair, Piece, PieceKind, Position,
+};
+use crate::crazyhouse::DROPPABLE;
+use crate::shared::{File, Rank};
+use crate::variant::Variant;
+
+pub const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FenError {
+    MissingField(&'static str),
+    InvalidPiecePlacement(String),
+    InvalidSideToMove(String),
+    InvalidCastling(String),
+    InvalidEnPassant(String),
+    InvalidCheckCount(String),
+    InvalidHalfmoveClock(String),
+    InvalidFullmoveNumber(String),
+    MissingKing(Color),
+    /// The FEN has pocket or check-count syntax the requested variant does
+    /// not use.
+    VariantMismatch(Variant),
+}
+
+impl Display for FenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FenError::MissingField(field) => write!(f, "missing {} field", field),
+            FenError::InvalidPiecePlacement(s) => write!(f, "invalid piece placement '{}'", s),
+            FenError::InvalidSideToMove(s) => write!(f, "invalid side to move '{}'", s),
+            FenError::InvalidCastling(s) => write!(f, "invalid castling rights '{}'", s),
+            FenError::InvalidEnPassant(s) => write!(f, "invalid en passant square '{}'", s),
+            FenError::InvalidCheckCount(s) => write!(f, "invalid check count '{}'", s),
+            FenError::InvalidHalfmoveClock(s) => write!(f, "invalid halfmove clock '{}'", s),
+            FenError::InvalidFullmoveNumber(s) => write!(f, "invalid fullmove number '{}'", s),
+            FenError::MissingKing(color) => write!(f, "no {:?} king on the board", color),
+            FenError::VariantMismatch(variant) => {
+                write!(f, "FEN does not describe a {} position", variant.name())
+            }
+        }
+    }
+}
+
+impl std::error::Error for FenError {}
+
+/// How the castling field is written out.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CastlingNotation {
+    /// X-FEN: `KQkq`, using the rook file only when the castling rook is
+    /// not the outermost one on that side of the king.
+    XFen,
+    /// Shredder-FEN: always the rook file, e.g. `HAha`.
+    Shredder,
+}
+
+impl Board {
+    /// Parses a FEN string. The castling field accepts standard `KQkq`,
+    /// X-FEN and Shredder-FEN rook files. The halfmove clock and fullmove
+    /// number may be omitted, as in EPD.
+    ///
+    /// A pocket in brackets after the placement, e.g. `.../R3K2R[Qnp]`,
+    /// makes the board a crazyhouse one; `~` after a knight, bishop, rook
+    /// or queen marks it as promoted, and is only allowed with a pocket. A
+    /// `3+2` field after the en passant square gives the checks white and
+    /// black still need at three-check.
+    pub fn from_fen(fen: &str) -> Result<Self, FenError> {
+        Board::parse_fen(fen, Variant::Standard)
+    }
+
+    /// Parses a FEN for `variant`, which decides whether kings may be
+    /// missing or repeated. A pocket is only accepted for crazyhouse and a
+    /// check count only for three-check.
+    pub fn from_variant_fen(fen: &str, variant: Variant) -> Result<Self, FenError> {
+        let board = Board::parse_fen(fen, variant)?;
+        if board.variant != variant {
+            return Err(FenError::VariantMismatch(variant));
+        }
+        Ok(board)
+    }
+
+    /// Parses a FEN starting out as `variant`, switching to crazyhouse or
+    /// three-check when the FEN has their syntax.
+    fn parse_fen(fen: &str, variant: Variant) -> Result<Self, FenError> {
+        let mut fields = fen.split_whitespace();
+        let placement = fields
+            .next()
+            .ok_or(FenError::MissingField("piece placement"))?;
+        let turn = fields
+            .next()
+            .ok_or(FenError::MissingField("side to move"))?;
+        let castling = fields.next().ok_or(FenError::MissingField("castling"))?;
+        let en_passant = fields.next().ok_or(FenError::MissingField("en passant"))?;
+        let mut fields = fields.peekable();
+        let checks = fields.next_if(|field| field.contains('+'));
+        let halfmove = fields.next().unwrap_or("0");
+        let fullmove = fields.next().unwrap_or("1");
+
+        let mut board = Board::empty();
+        board.variant = variant;
+        parse_placement(&mut board, placement)?;
+
+        board.turn = match turn {
+            "w" => Color::White,
+            "b" => Color::Black,
+            _ => return Err(FenError::InvalidSideToMove(turn.to_string())),
+        };
+        board.castling_rights = parse_castling(&board, castling)?;
+        board.en_passant_target = parse_en_passant(en_passant)?;
+        if let Some(checks) = checks {
+            parse_checks(&mut board, checks)?;
+        }
+        board.fifty_moves = halfmove
+            .parse()
+            .map_err(|_| FenError::InvalidHalfmoveClock(halfmove.to_string()))?;
+
+        let invalid_fullmove = || FenError::InvalidFullmoveNumber(fullmove.to_string());
+        let fullmove: u32 = fullmove.parse().map_err(|_| invalid_fullmove())?;
+        board.ply = fullmove
+            .saturating_sub(1)
+            .checked_mul(2)
+            .and_then(|ply| ply.checked_add((board.turn == Color::Black) as u32))
+            .ok_or_else(invalid_fullmove)?;
+
+        Ok(board)
+    }
+
+    /// FEN with the castling field in X-FEN notation, which is plain `KQkq`
+    /// for standard chess.
+    pub fn to_fen(&self) -> String {
+        self.to_fen_with(CastlingNotation::XFen)
+    }
+
+    pub fn to_shredder_fen(&self) -> String {
+        self.to_fen_with(CastlingNotation::Shredder)
+    }
+
+    pub fn to_fen_with(&self, notation: CastlingNotation) -> String {
+        let mut placement = String::new();
+
+        for rank in Rank::iter().rev() {
+            let mut empty = 0;
+
+            for file in File::iter() {
+                match self.piece_at(Position::new(file, rank)) {
+                    Some(piece) => {
+                        if empty > 0 {
+                            placement.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        placement.push(piece.to_char());
+                        if self.variant.has_pockets()
+                            && self
+                                .promoted
+                                .is_set(Position::new(file, rank).to_64_index())
+                        {
+                            placement.push('~');
+                        }
+                    }
+                    None => empty += 1,
+                }
+            }
+
+            if empty > 0 {
+                placement.push_str(&empty.to_string());
+            }
+            if rank != Rank::One {
+                placement.push('/');
+            }
+        }
+
+        if self.variant.has_pockets() {
+            placement.push('[');
+            for color in [Color::White, Color::Black] {
+                for kind in DROPPABLE.iter().rev() {
+                    let piece = Piece::new(*kind, color);
+                    for _ in 0..self.pocket(piece) {
+                        placement.push(piece.to_char());
+                    }
+                }
+            }
+            placement.push(']');
+        }
+
+        let turn = match self.turn {
+            Color::White => 'w',
+            Color::Black => 'b',
+        };
+
+        let en_passant = self
+            .en_passant_target
+            .map_or("-".to_string(), |target| target.to_string());
+
+        let en_passant = match self.variant {
+            Variant::ThreeCheck => format!(
+                "{} {}+{}",
+                en_passant, self.checks_remaining.white, self.checks_remaining.black
+            ),
+            _ => en_passant,
+        };
+
+        format!(
+            "{} {} {} {} {} {}",
+            placement,
+            turn,
+            self.castling_field(notation),
+            en_passant,
+            self.fifty_moves,
+            self.ply / 2 + 1
+        )
+    }
+
+    fn castling_field(&self, notation: CastlingNotation) -> String {
+        let mut field = String::new();
+
+        for color in [Color::White, Color::Black] {
+            for side in [CastlingSide::King, CastlingSide::Queen] {
+                let Some(file) = self.castling_rights.rook_file(color, side) else {
+                    continue;
+                };
+
+                let c = match (notation, side) {
+                    (CastlingNotation::XFen, CastlingSide::King)
+                        if outermost_rook(self, color, side) == Some(file) =>
+                    {
+                        'k'
+                    }
+                    (CastlingNotation::XFen, CastlingSide::Queen)
+                        if outermost_rook(self, color, side) == Some(file) =>
+                    {
+                        'q'
+                    }
+                    _ => file.to_char(),
+                };
+
+                field.push(match color {
+                    Color::White => c.to_ascii_uppercase(),
+                    Color::Black => c,
+                });
+            }
+        }
+
+        if field.is_empty() {
+            field.push('-');
+        }
+        field
+    }
+}
+
+fn parse_placement(board: &mut Board, placement: &str) -> Result<(), FenError> {
+    let invalid = || FenError::InvalidPiecePlacement(placement.to_string());
+
+    let (placement, pocket) = match placement.strip_suffix(']') {
+        Some(rest) => rest
+            .split_once('[')
+            .map(|(placement, pocket)| (placement, Some(pocket)))
+            .ok_or_else(invalid)?,
+        None => (placement, None),
+    };
+    let rows: Vec<&str> = placement.split('/').collect();
+    if rows.len() != 8 {
+        return Err(invalid());
+    }
+
+    let mut kings = ColoredPair::<u8>::default();
+    // Only crazyhouse tracks promoted pieces.
+    let has_pockets = pocket.is_some() || board.variant.has_pockets();
+
+    for (row, rank) in rows.iter().zip(Rank::iter().rev()) {
+        let mut files = File::iter();
+        let mut last = None;
+
+        for c in row.chars() {
+            if c == '~' {
+                let (position, piece): (Position, Piece) = last.take().ok_or_else(invalid)?;
+                if !has_pockets || matches!(piece.kind, PieceKind::Pawn | PieceKind::King) {
+                    return Err(invalid());
+                }
+                board.promoted.set(position.to_64_index());
+                continue;
+            }
+            last = None;
+
+            if let Some(skip) = c.to_digit(10) {
+                if skip == 0 || skip > 8 {
+                    return Err(invalid());
+                }
+                for _ in 0..skip {
+                    files.next().ok_or_else(invalid)?;
+                }
+                continue;
+            }
+
+            let piece = Piece::from_char(c).ok_or_else(invalid)?;
+            let position = Position::new(files.next().ok_or_else(invalid)?, rank);
+
+            if piece.kind == PieceKind::King {
+                *kings.get_mut(piece.color) += 1;
+            }
+
+            board.put_piece(piece, position);
+            last = Some((position, piece));
+        }
+
+        if files.next().is_some() {
+            return Err(invalid());
+        }
+    }
+
+    if let Some(pocket) = pocket {
+        board.variant = Variant::Crazyhouse;
+        for c in pocket.chars() {
+            let piece = Piece::from_char(c).ok_or_else(invalid)?;
+            if !board.add_to_pocket(piece) {
+                return Err(invalid());
+            }
+        }
+    }
+
+    for color in [Color::White, Color::Black] {
+        if !board.variant.requires_king(color) {
+            continue;
+        }
+        match kings.get(color) {
+            0 if board.variant.king_can_vanish() => {}
+            0 => return Err(FenError::MissingKing(color)),
+            1 => {}
+            _ => return Err(invalid()),
+        }
+    }
+    Ok(())
+}
+
+fn parse_castling(board: &Board, field: &str) -> Result<CastlingRights, FenError> {
+    let mut rights = CastlingRights::none();
+    if field == "-" {
+        return Ok(rights);
+    }
+
+    let invalid = || FenError::InvalidCastling(field.to_string());
+
+    for c in field.chars() {
+        let color = if c.is_ascii_uppercase() {
+            Color::White
+        } else {
+            Color::Black
+        };
+
+        let king = board.king(color).ok_or_else(invalid)?;
+        if king.rank != back_rank(color) {
+            return Err(invalid());
+        }
+
+        let rook_file = match c.to_ascii_lowercase() {
+            'k' => outermost_rook(board, color, CastlingSide::King),
+            'q' => outermost_rook(board, color, CastlingSide::Queen),
+            f => File::from_char(f).filter(|&file| {
+                board.piece_at(Position::new(file, king.rank)) == Some(rook(color))
+            }),
+        }
+        .ok_or_else(invalid)?;
+
+        let side = if rook_file > king.file {
+            CastlingSide::King
+        } else {
+            CastlingSide::Queen
+        };
+        rights.set(color, side, Some(rook_file));
+    }
+
+    Ok(rights)
+}
+
+fn parse_en_passant(field: &str) -> Result<Option<Position>, FenError> {
+    if field == "-" {
+        return Ok(None);
+    }
+
+    let invalid = || FenError::InvalidEnPassant(field.to_string());
+
+    let square: Position = field.parse().map_err(|_| invalid())?;
+    if !matches!(square.rank, Rank::Three | Rank::Six) {
+        return Err(invalid());
+    }
+
+    Ok(Some(square))
+}
+
+fn parse_checks(board: &mut Board, field: &str) -> Result<(), FenError> {
+    let invalid = || FenError::InvalidCheckCount(field.to_string());
+
+    let (white, black) = field.split_once('+').ok_or_else(invalid)?;
+    for (color, count) in [(Color::White, white), (Color::Black, black)] {
+        let count: u8 = count.parse().map_err(|_| invalid())?;
+        if count > 3 {
+            return Err(invalid());
+        }
+        board.set_checks_remaining(color, count);
+    }
+
+    board.variant = Variant::ThreeCheck;
+    Ok(())
+}
+
+fn back_rank(color: Color) -> Rank {
+    match color {
+        Color::White => Rank::One,
+        Color::Black => Rank::Eight,
+    }
+}
+
+fn rook(color: Color) -> Piece {
+    Piece::new(PieceKind::Rook, color)
+}
+
+/// The rook furthest from the king on the given side of its back rank,
+/// which is what `K`/`Q` refer to in X-FEN.
+fn outermost_rook(board: &Board, color: Color, side: CastlingSide) -> Option<File> {
+    let king = board.king(color)?;
+    let mut rooks = File::iter()
+        .filter(|&file| board.piece_at(Position::new(file, king.rank)) == Some(rook(color)));
+
+    match side {
+        CastlingSide::King => rooks.filter(|&file| file > king.file).last(),
+        CastlingSide::Queen => rooks.find(|&file| file < king.file),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_start_position_round_trip() {
+        let board = Board::from_fen(START_FEN).unwrap();
+
+        assert_eq!(board.to_fen(), START_FEN);
+        assert_eq!(Board::new().to_fen(), START_FEN);
+        assert_eq!(board.castling_rights, CastlingRights::standard());
+    }
+
+    #[test]
+    fn test_fen_round_trip() {
+        let fens = [
+            "rnbqkbnr/pp1ppppp/8/2p5/4P3/8/PPPP1PPP/RNBQKBNR w KQkq c6 0 2",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "4k3/8/8/8/8/8/8/4K2R b K - 12 40",
+        ];
+
+        for fen in fens {
+            assert_eq!(Board::from_fen(fen).unwrap().to_fen(), fen);
+        }
+    }
+
+    #[test]
+    fn test_fen_kings_and_turn() {
+        let board = Board::from_fen("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 b - - 3 17").unwrap();
+
+        assert_eq!(board.kings.white, Some(Position::new(File::A, Rank::Five)));
+        assert_eq!(board.kings.black, Some(Position::new(File::H, Rank::Four)));
+        assert_eq!(board.turn, Color::Black);
+        assert_eq!(board.fifty_moves, 3);
+        assert_eq!(board.ply, 33);
+    }
+
+    #[test]
+    fn test_fen_without_clocks() {
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - -").unwrap();
+
+        assert_eq!(board.to_fen(), "4k3/8/8/8/8/8/8/4K3 w - - 0 1");
+    }
+
+    #[test]
+    fn test_huge_fullmove_number() {
+        assert_eq!(
+            Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 4294967295").unwrap_err(),
+            FenError::InvalidFullmoveNumber("4294967295".to_string())
+        );
+
+        // The largest number whose ply count still fits.
+        let fen = "4k3/8/8/8/8/8/8/4K3 b - - 0 2147483648";
+        assert_eq!(Board::from_fen(fen).unwrap().to_fen(), fen);
+    }
+
+    #[test]
+    fn test_shredder_fen() {
+        let fen = "bqnbrkrn/pppppppp/8/8/8/8/PPPPPPPP/BQNBRKRN w GEge - 0 1";
+        let board = Board::from_fen(fen).unwrap();
+
+        assert_eq!(board.castling_rights.white_king_side, Some(File::G));
+        assert_eq!(board.castling_rights.white_queen_side, Some(File::E));
+        assert_eq!(board.castling_rights.black_king_side, Some(File::G));
+        assert_eq!(board.castling_rights.black_queen_side, Some(File::E));
+        assert_eq!(board.to_shredder_fen(), fen);
+        assert_eq!(
+            board.to_fen(),
+            "bqnbrkrn/pppppppp/8/8/8/8/PPPPPPPP/BQNBRKRN w KQkq - 0 1"
+        );
+        assert_eq!(
+            Board::new().to_shredder_fen().split(' ').nth(2),
+            Some("HAha")
+        );
+    }
+
+    #[test]
+    fn test_x_fen_inner_rook() {
+        // Two rooks on the king side: castling with the inner one must name its file.
+        let fen = "4k3/8/8/8/8/8/8/R3K1RR w G - 0 1";
+        let board = Board::from_fen(fen).unwrap();
+
+        assert_eq!(board.castling_rights.white_king_side, Some(File::G));
+        assert_eq!(board.castling_rights.white_queen_side, None);
+        assert_eq!(board.to_fen(), "4k3/8/8/8/8/8/8/R3K1RR w G - 0 1");
+
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K1RR w K - 0 1").unwrap();
+        assert_eq!(board.castling_rights.white_king_side, Some(File::H));
+    }
+
+    #[test]
+    fn test_crazyhouse_fen() {
+        let fen = "r1bqk2r/pppp1ppp/2n2n2/4p3/1bB1P3/2N2N2/PPPP1PPP/R1BQK2R~[Pn] w KQkq - 0 5";
+        let board = Board::from_fen(fen).unwrap();
+
+        assert_eq!(board.variant, Variant::Crazyhouse);
+        assert_eq!(board.pocket(Piece::new(PieceKind::Pawn, Color::White)), 1);
+        assert_eq!(board.pocket(Piece::new(PieceKind::Knight, Color::Black)), 1);
+        assert_eq!(board.pocket(Piece::new(PieceKind::Knight, Color::White)), 0);
+        assert!(board.promoted.is_set(7));
+        assert_eq!(board.to_fen(), fen);
+
+        let empty = Board::from_fen("4k3/8/8/8/8/8/8/4K3[] w - - 0 1").unwrap();
+        assert_eq!(empty.variant, Variant::Crazyhouse);
+        assert_eq!(empty.to_fen(), "4k3/8/8/8/8/8/8/4K3[] w - - 0 1");
+
+        for bad in [
+            "4k3/8/8/8/8/8/8/4K3[K] w - - 0 1",
+            "4k3/8/8/8/8/8/8/4K3[x] w - - 0 1",
+            "4k3/8/8/8/8/8/8/4K3] w - - 0 1",
+            "4k3/8/8/8/8/8/8/~4K3[] w - - 0 1",
+            "4k3/8/8/8/8/8/P~7/4K3[] w - - 0 1",
+            "4k3/8/8/8/8/8/8/4K~3[] w - - 0 1",
+            "4k2Q~/8/8/8/8/8/8/4K3 w - - 0 1",
+            "4k3/8/8/8/8/8/8/4K3[PPPPPPPPPPPPPPPPP] w - - 0 1",
+        ] {
+            assert!(
+                matches!(
+                    Board::from_fen(bad),
+                    Err(FenError::InvalidPiecePlacement(_))
+                ),
+                "{}",
+                bad
+            );
+        }
+    }
+
+    #[test]
+    fn test_three_check_fen() {
+        let fen = "rnbqkbnr/ppp2ppp/8/3pp3/4P3/5Q2/PPPP1PPP/RNB1KBNR b KQkq - 3+2 1 3";
+        let board = Board::from_fen(fen).unwrap();
+
+        assert_eq!(board.variant, Variant::ThreeCheck);
+        assert_eq!(board.checks_remaining.white, 3);
+        assert_eq!(board.checks_remaining.black, 2);
+        assert_eq!(board.to_fen(), fen);
+
+        let fresh = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 3+3 0 1").unwrap();
+        let checked = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 2+3 0 1").unwrap();
+        let standard = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(fresh.position_key, standard.position_key);
+        assert_ne!(checked.position_key, fresh.position_key);
+
+        for bad in ["4+3", "3+", "a+1", "3-3+"] {
+            assert_eq!(
+                Board::from_fen(&format!("4k3/8/8/8/8/8/8/4K3 w - - {} 0 1", bad)).unwrap_err(),
+                FenError::InvalidCheckCount(bad.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn test_variant_fen_must_match() {
+        let pocket = "4k3/8/8/8/8/8/8/4K3[Pn] w - - 0 1";
+        let checks = "4k3/8/8/8/8/8/8/4K3 w - - 2+3 0 1";
+
+        assert!(Board::from_variant_fen(pocket, Variant::Crazyhouse).is_ok());
+        assert!(Board::from_variant_fen(checks, Variant::ThreeCheck).is_ok());
+        assert_eq!(
+            Board::from_variant_fen(pocket, Variant::Standard).unwrap_err(),
+            FenError::VariantMismatch(Variant::Standard)
+        );
+        assert_eq!(
+            Board::from_variant_fen(checks, Variant::Atomic).unwrap_err(),
+            FenError::VariantMismatch(Variant::Atomic)
+        );
+
+        let plain = "4k3/8/8/8/8/8/8/4K3 w - - 0 1";
+        let board = Board::from_variant_fen(plain, Variant::Crazyhouse).unwrap();
+        assert_eq!(board.variant, Variant::Crazyhouse);
+    }
+
+    #[test]
+    fn test_pocket_changes_position_key() {
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/4K3[] w - - 0 1").unwrap();
+        let with_pawn = Board::from_fen("4k3/8/8/8/8/8/8/4K3[P] w - - 0 1").unwrap();
+        let with_black_pawn = Board::from_fen("4k3/8/8/8/8/8/8/4K3[p] w - - 0 1").unwrap();
+
+        assert_ne!(board.position_key, with_pawn.position_key);
+        assert_ne!(with_pawn.position_key, with_black_pawn.position_key);
+
+        let mut captured = Board::from_fen("4k3/8/8/8/8/8/8/4K3[] w - - 0 1").unwrap();
+        assert!(captured.add_to_pocket(Piece::new(PieceKind::Pawn, Color::White)));
+        assert_eq!(captured.position_key, with_pawn.position_key);
+    }
+
+    #[test]
+    fn test_fen_errors() {
+        assert_eq!(
+            Board::from_fen("").unwrap_err(),
+            FenError::MissingField("piece placement")
+        );
+        assert!(matches!(
+            Board::from_fen("8/8/8/8/8/8/8 w - - 0 1"),
+            Err(FenError::InvalidPiecePlacement(_))
+        ));
+        assert!(matches!(
+            Board::from_fen("4k3/8/8/8/8/8/8/4K4 w - - 0 1"),
+            Err(FenError::InvalidPiecePlacement(_))
+        ));
+        assert_eq!(
+            Board::from_fen("4k3/8/8/8/8/8/8/8 w - - 0 1").unwrap_err(),
+            FenError::MissingKing(Color::White)
+        );
+        assert!(matches!(
+            Board::from_fen("4k3/8/8/8/8/8/8/4K3 x - - 0 1"),
+            Err(FenError::InvalidSideToMove(_))
+        ));
+        assert!(matches!(
+            Board::from_fen("4k3/8/8/8/8/8/8/4K3 w K - 0 1"),
+            Err(FenError::InvalidCastling(_))
+        ));
+        assert!(matches!(
+            Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - e4 0 1"),
+            Err(FenError::InvalidEnPassant(_))
+        ));
+        assert!(matches!(
+            Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - - x 1"),
+            Err(FenError::InvalidHalfmoveClock(_))
+        ));
+    }
+
+    mod fuzz {
+        use super::*;
+        use crate::eval::{Evaluate, Evaluator};
+        use proptest::prelude::*;
+
+        const KINDS: [PieceKind; 5] = [
+            PieceKind::Pawn,
+            PieceKind::Knight,
+            PieceKind::Bishop,
+            PieceKind::Rook,
+            PieceKind::Queen,
+        ];
+
+        /// Random placements with one king per side and any other piece,
+        /// pawns included, on any square; castling rights for rooks that
+        /// share the king's back rank; any en passant file; and clocks over
+        /// their whole range.
+        fn arb_board() -> impl Strategy<Value = Board> {
+            (
+                prop::collection::vec(
+                    prop_oneof![6 => Just(None), 1 => (0usize..10).prop_map(Some)],
+                    64,
+                ),
+                (0usize..64, 0usize..64).prop_filter("kings apart", |(w, b)| w != b),
+                any::<[bool; 4]>(),
+                prop::option::of(0usize..8),
+                any::<u8>(),
+                any::<u32>(),
+            )
+                .prop_map(|(pieces, kings, castling, en_passant, fifty_moves, ply)| {
+                    let mut board = Board::empty();
+                    for (index, piece) in pieces.into_iter().enumerate() {
+                        if let Some(piece) = piece
+                            && index != kings.0
+                            && index != kings.1
+                        {
+                            let color = if piece < 5 {
+                                Color::White
+                            } else {
+                                Color::Black
+                            };
+                            board.put_piece(
+                                Piece::new(KINDS[piece % 5], color),
+                                Position::from_64_index(index),
+                            );
+                        }
+                    }
+                    for (index, color) in [(kings.0, Color::White), (kings.1, Color::Black)] {
+                        board.put_piece(
+                            Piece::new(PieceKind::King, color),
+                            Position::from_64_index(index),
+                        );
+                    }
+
+                    board.turn = if ply % 2 == 1 {
+                        Color::Black
+                    } else {
+                        Color::White
+                    };
+                    for (i, (color, side)) in [
+                        (Color::White, CastlingSide::King),
+                        (Color::White, CastlingSide::Queen),
+                        (Color::Black, CastlingSide::King),
+                        (Color::Black, CastlingSide::Queen),
+                    ]
+                    .into_iter()
+                    .enumerate()
+                    {
+                        let king = board.king(color).unwrap();
+                        if !castling[i] || king.rank != back_rank(color) {
+                            continue;
+                        }
+                        let rook = board.pieces().find(|(position, piece)| {
+                            *piece == Piece::new(PieceKind::Rook, color)
+                                && position.rank == king.rank
+                                && (position.file > king.file) == (side == CastlingSide::King)
+                        });
+                        if let Some((position, _)) = rook {
+                            board.castling_rights.set(color, side, Some(position.file));
+                        }
+                    }
+
+                    board.en_passant_target = en_passant.map(|file| {
+                        let rank = match board.turn {
+                            Color::White => Rank::Six,
+                            Color::Black => Rank::Three,
+                        };
+                        Position::from_64_index(rank as usize * 8 + file)
+                    });
+                    board.fifty_moves = fifty_moves;
+                    board.ply = ply;
+                    board
+                })
+        }
+
+        fn assert_same(parsed: &Board, board: &Board) {
+            assert_eq!(
+                parsed.pieces().collect::<Vec<_>>(),
+                board.pieces().collect::<Vec<_>>()
+            );
+            assert_eq!(parsed.turn, board.turn);
+            assert_eq!(parsed.castling_rights, board.castling_rights);
+            assert_eq!(parsed.en_passant_target, board.en_passant_target);
+            assert_eq!(parsed.fifty_moves, board.fifty_moves);
+            assert_eq!(parsed.ply, board.ply);
+            assert_eq!(parsed.pawn_key, board.pawn_key);
+            assert_eq!(parsed.pawns.both, board.pawns.both);
+            assert_eq!(parsed.kings.white, board.kings.white);
+            assert_eq!(parsed.kings.black, board.kings.black);
+        }
+
+        proptest! {
+            #[test]
+            fn fen_round_trip(board in arb_board()) {
+                let fen = board.to_fen();
+                let parsed = Board::from_fen(&fen).unwrap();
+
+                prop_assert_eq!(parsed.to_fen(), fen);
+                assert_same(&parsed, &board);
+            }
+
+            #[test]
+            fn shredder_fen_round_trip(board in arb_board()) {
+                let fen = board.to_shredder_fen();
+                let parsed = Board::from_fen(&fen).unwrap();
+
+                prop_assert_eq!(parsed.to_shredder_fen(), fen);
+                assert_same(&parsed, &board);
+            }
+
+            #[test]
+            fn clocks_never_panic(
+                board in arb_board(),
+                halfmove in prop_oneof![0u32..=255, any::<u32>()],
+                fullmove in prop_oneof![
+                    any::<u32>(),
+                    (1u32 << 31) - 2..=(1u32 << 31) + 2,
+                    u32::MAX - 2..=u32::MAX,
+                ],
+            ) {
+                let fields: Vec<String> = board.to_fen().split(' ').map(String::from).collect();
+                let fen = format!("{} {} {}", fields[..4].join(" "), halfmove, fullmove);
+
+                if let Ok(parsed) = Board::from_fen(&fen) {
+                    prop_assert_eq!(parsed.fifty_moves as u32, halfmove);
+                    prop_assert!(Board::from_fen(&parsed.to_fen()).is_ok());
+                }
+            }
+
+            #[test]
+            fn evaluation_never_panics(board in arb_board()) {
+                let board = Board::from_fen(&board.to_fen()).unwrap();
+                let mut evaluator = Evaluator::new();
+
+                let score = match board.turn {
+                    Color::White => evaluator.evaluate(&board),
+                    Color::Black => -evaluator.evaluate(&board),
+                };
+                prop_assert_eq!(evaluator.trace(&board).total(), score);
+                #[cfg(feature = "tui")]
+                for color in [Color::White, Color::Black] {
+                    crate::threats::Threats::of(&board, color);
+                }
+            }
+
+            #[test]
+            fn parser_never_panics(input in "[ -~]{0,90}") {
+                let _ = Board::from_fen(&input);
+            }
+
+            #[test]
+            fn mutated_fen_never_panics(
+                board in arb_board(),
+                index in any::<prop::sample::Index>(),
+                replacement in prop::sample::select(
+                    "pnbrqkPNBRQK0123456789/ -wbKQkqacehx".chars().collect::<Vec<_>>()
+                ),
+            ) {
+                let mut fen: Vec<char> = board.to_fen().chars().collect();
+                let i = index.index(fen.len());
+                fen[i] = replacement;
+
+                if let Ok(parsed) = Board::from_fen(&fen.into_iter().collect::<String>()) {
+                    prop_assert!(Board::from_fen(&parsed.to_fen()).is_ok());
+                }
+            }
+        }
+    }
+}
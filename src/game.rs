@@ -0,0 +1,75 @@
+//! A game record: start position, moves played and PGN-style tags.
+
+use std::collections::BTreeMap;
+
+use crate::board::Board;
+use crate::fen::{FenError, START_FEN};
+use crate::moves::Move;
+use crate::variant::{Outcome, Variant};
+
+/// With the `serde` feature, moves are written in UCI notation and
+/// checked against the position when read back.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Game {
+    pub variant: Variant,
+    /// Kept as FEN rather than a `Board` so the record stays cheap to
+    /// clone and compare.
+    pub start_fen: String,
+    pub moves: Vec<Move>,
+    /// Tags such as `Event`, `White` or `Date`, sorted by name.
+    pub tags: BTreeMap<String, String>,
+    pub outcome: Option<Outcome>,
+}
+
+impl Game {
+    /// A game from the start position of `variant`, with no moves yet.
+    pub fn new(variant: Variant) -> Self {
+        Game::from_fen(variant, variant.start_fen())
+    }
+
+    pub fn from_fen(variant: Variant, fen: &str) -> Self {
+        Game {
+            variant,
+            start_fen: fen.to_string(),
+            moves: Vec::new(),
+            tags: BTreeMap::new(),
+            outcome: None,
+        }
+    }
+
+    pub fn start_board(&self) -> Result<Board, FenError> {
+        Board::from_variant_fen(&self.start_fen, self.variant)
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags.get(name).map(String::as_str)
+    }
+
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        self.tags.insert(name.to_string(), value.to_string());
+    }
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Game::from_fen(Variant::Standard, START_FEN)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_game_start_board() {
+        let game = Game::new(Variant::Horde);
+        assert_eq!(game.start_board().unwrap().to_fen(), game.start_fen);
+        assert_eq!(game.start_board().unwrap().variant, Variant::Horde);
+
+        let mut game = Game::default();
+        game.set_tag("White", "Andrej");
+        assert_eq!(game.tag("White"), Some("Andrej"));
+        assert_eq!(game.tag("Black"), None);
+        assert_eq!(game.start_board().unwrap().to_fen(), START_FEN);
+    }
+}
@@ -0,0 +1,56 @@
+//! Andrej chess engine as a library: board representation, FEN, move
+//! encoding, attacks, evaluation and variant rules.
+//!
+//! Most users only need the `prelude`:
+//!
+//! ```
+//! use andrej::prelude::*;
+//!
+//! let board = Board::from_fen(START_FEN).unwrap();
+//! assert_eq!(board.turn, Color::White);
+//! assert_eq!(Evaluator::new().evaluate(&board), 0);
+//! ```
+//!
+//! Cargo features, all on by default:
+//!
+//! - `tui`: terminal rendering (`render`, `threats`). Without it `Board`
+//!   displays as its FEN.
+//! - `nnue`: the NNUE evaluation backend (`eval::nnue`).
+//!
+//! The `serde` feature, off by default, adds `Serialize` and `Deserialize`
+//! for pieces, squares, boards (as FEN) and `game::Game` records, with
+//! moves written in UCI notation.
+
+pub mod atomic;
+pub mod attacks;
+pub mod bitboard;
+pub mod board;
+pub mod crazyhouse;
+pub mod datagen;
+pub mod eval;
+pub mod fen;
+pub mod game;
+pub mod makemove;
+pub mod moves;
+#[cfg(feature = "tui")]
+pub mod render;
+#[cfg(feature = "serde")]
+mod serialize;
+pub mod shared;
+#[cfg(feature = "tui")]
+pub mod threats;
+pub mod tune;
+pub mod variant;
+pub mod zobrist;
+
+/// The types needed to load a position, inspect it and evaluate it.
+pub mod prelude {
+    pub use crate::bitboard::Bitboard;
+    pub use crate::board::{Board, CastlingSide, Color, Piece, PieceKind, Position};
+    pub use crate::eval::{Evaluate, Evaluator};
+    pub use crate::fen::{FenError, START_FEN};
+    pub use crate::game::Game;
+    pub use crate::moves::{CrazyhouseMoveList, Move, MoveList};
+    pub use crate::shared::{File, Rank};
+    pub use crate::variant::{Outcome, Variant};
+}
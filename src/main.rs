@@ -1,10 +1,216 @@
-mod bitboard;
-mod board;
-mod shared;
-
-use board::Board;
+use andrej::board::Board;
+#[cfg(feature = "tui")]
+use andrej::board::Color;
+use andrej::datagen;
+#[cfg(feature = "nnue")]
+use andrej::eval::nnue::Nnue;
+use andrej::eval::params::EvalParams;
+use andrej::eval::{Evaluate, Evaluator};
+#[cfg(feature = "tui")]
+use andrej::render::{BoardRenderer, Style};
+#[cfg(feature = "tui")]
+use andrej::threats::Threats;
+use andrej::tune::{self, TuneOptions};
 
 fn main() {
-    let board = Board::new();
-    println!("{}", board);
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    match args.first().map(String::as_str) {
+        Some("eval") => match &args[1..] {
+            #[cfg(feature = "nnue")]
+            [flag, path, fen @ ..] if flag == "--eval-file" => {
+                let board = parse_board(fen);
+                let mut nnue = Nnue::load(path).unwrap_or_else(|err| {
+                    eprintln!("error: {}", err);
+                    std::process::exit(1);
+                });
+                println!("{}", board);
+                println!("NNUE evaluation (side to move): {}", nnue.evaluate(&board));
+            }
+            [flag, path, fen @ ..] if flag == "--params" => {
+                let board = parse_board(fen);
+                let mut evaluator = Evaluator::with_params(load_params(path));
+                println!("{}", board);
+                print!("{}", evaluator.trace(&board));
+                println!("Evaluation (side to move): {}", evaluator.evaluate(&board));
+            }
+            fen => {
+                let board = parse_board(fen);
+                let mut evaluator = Evaluator::new();
+                println!("{}", board);
+                print!("{}", evaluator.trace(&board));
+                println!("Evaluation (side to move): {}", evaluator.evaluate(&board));
+            }
+        },
+        Some("tune") => run_tune(&args[1..]),
+        Some("datagen") => run_datagen(&args[1..]),
+        #[cfg(feature = "tui")]
+        Some("show") => run_show(&args[1..]),
+        _ => {
+            let board = Board::new();
+            println!("{}", board);
+        }
+    }
+}
+
+/// Board from a FEN given as one or more arguments, or the start position.
+fn parse_board(args: &[String]) -> Board {
+    if args.is_empty() {
+        return Board::new();
+    }
+
+    Board::from_fen(&args.join(" ")).unwrap_or_else(|err| {
+        eprintln!("error: {}", err);
+        std::process::exit(1);
+    })
+}
+
+fn load_params(path: &str) -> EvalParams {
+    EvalParams::load(path).unwrap_or_else(|err| {
+        eprintln!("error: {}: {}", path, err);
+        std::process::exit(1);
+    })
+}
+
+/// `tune <positions> [--epochs N] [--learning-rate X] [--params FILE]
+/// [--output FILE]`
+fn run_tune(args: &[String]) {
+    let usage = || -> ! {
+        eprintln!(
+            "usage: andrej tune <positions> [--epochs N] [--learning-rate X] \
+             [--params FILE] [--output FILE]"
+        );
+        std::process::exit(1);
+    };
+
+    let mut positions = None;
+    let mut options = TuneOptions::default();
+    let mut start = EvalParams::default();
+    let mut output = String::from("params.toml");
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--epochs" => {
+                options.epochs = args
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .unwrap_or_else(|| usage())
+            }
+            "--learning-rate" => {
+                options.learning_rate = args
+                    .next()
+                    .and_then(|x| x.parse().ok())
+                    .unwrap_or_else(|| usage())
+            }
+            "--params" => start = load_params(args.next().unwrap_or_else(|| usage())),
+            "--output" => output = args.next().unwrap_or_else(|| usage()).clone(),
+            path if positions.is_none() => positions = Some(path.to_string()),
+            _ => usage(),
+        }
+    }
+    let positions = positions.unwrap_or_else(|| usage());
+
+    let samples = std::fs::read_to_string(&positions)
+        .map_err(|err| err.to_string())
+        .and_then(|input| tune::load_samples(&input).map_err(|err| err.to_string()))
+        .unwrap_or_else(|err| {
+            eprintln!("error: {}: {}", positions, err);
+            std::process::exit(1);
+        });
+    println!("Loaded {} positions", samples.len());
+
+    let tuned = tune::tune(&samples, &start, options, |epoch, error| {
+        if epoch % 100 == 0 || epoch == options.epochs {
+            println!("Epoch {}: error {:.6}", epoch, error);
+        }
+    })
+    .unwrap_or_else(|err| {
+        eprintln!("error: {}: {}", positions, err);
+        std::process::exit(1);
+    });
+
+    if let Err(err) = std::fs::write(&output, tuned.to_toml()) {
+        eprintln!("error: {}: {}", output, err);
+        std::process::exit(1);
+    }
+    println!("Wrote {}", output);
+}
+
+/// `datagen pack <text> <binary>` and `datagen unpack <binary> <text>`
+/// convert between the two training data formats.
+fn run_datagen(args: &[String]) {
+    let fail = |path: &str, err: String| -> ! {
+        eprintln!("error: {}: {}", path, err);
+        std::process::exit(1);
+    };
+
+    match args {
+        [command, input, output] if command == "pack" => {
+            let text =
+                std::fs::read_to_string(input).unwrap_or_else(|err| fail(input, err.to_string()));
+            let binary = datagen::text_to_binary(&text)
+                .unwrap_or_else(|(line, err)| fail(input, format!("line {}: {}", line, err)));
+            std::fs::write(output, &binary).unwrap_or_else(|err| fail(output, err.to_string()));
+            println!("Packed {} records", binary.len() / datagen::RECORD_SIZE);
+        }
+        [command, input, output] if command == "unpack" => {
+            let binary = std::fs::read(input).unwrap_or_else(|err| fail(input, err.to_string()));
+            let text = datagen::binary_to_text(&binary)
+                .unwrap_or_else(|(record, err)| fail(input, format!("record {}: {}", record, err)));
+            std::fs::write(output, &text).unwrap_or_else(|err| fail(output, err.to_string()));
+            println!("Unpacked {} records", text.lines().count());
+        }
+        _ => {
+            eprintln!("usage: andrej datagen pack <text> <binary>");
+            eprintln!("       andrej datagen unpack <binary> <text>");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// `show [--attacks <white|black>] [--style <truecolor|256|unicode|ascii>]
+/// [--flip] [fen]`
+#[cfg(feature = "tui")]
+fn run_show(args: &[String]) {
+    let usage = || -> ! {
+        eprintln!(
+            "usage: andrej show [--attacks <white|black>] \
+             [--style <truecolor|256|unicode|ascii>] [--flip] [fen]"
+        );
+        std::process::exit(1);
+    };
+
+    let mut attacks = None;
+    let mut style = Style::detect();
+    let mut flipped = false;
+    let mut fen = Vec::new();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--attacks" => {
+                attacks = match args.next().map(String::as_str) {
+                    Some("white") => Some(Color::White),
+                    Some("black") => Some(Color::Black),
+                    _ => usage(),
+                }
+            }
+            "--style" => {
+                style = args
+                    .next()
+                    .and_then(|name| Style::from_name(name))
+                    .unwrap_or_else(|| usage())
+            }
+            "--flip" => flipped = true,
+            _ => fen.push(arg.clone()),
+        }
+    }
+
+    let board = parse_board(&fen);
+    let renderer = BoardRenderer::new(style).flipped(flipped);
+    match attacks {
+        Some(color) => print!("{}", Threats::of(&board, color).render(&board, renderer)),
+        None => print!("{}", renderer.render(&board)),
+    }
 }
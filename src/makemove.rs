@@ -0,0 +1,267 @@
+//! Playing moves on a `Board`, with the rules of every variant: pocket
+//! drops and captures in crazyhouse, explosions in atomic and the check
+//! count at three-check.
+
+use crate::board::{Board, CastlingSide, Color, Piece, PieceKind, Position, Undo};
+use crate::moves::Move;
+use crate::shared::{File, Rank};
+use crate::variant::Variant;
+
+impl Board {
+    /// Plays `mv` for the side to move and records an `Undo` in `history`.
+    /// The move must fit the position, as from `Move::from_uci` or a move
+    /// generator; whether it leaves the king in check is not checked.
+    pub fn make_move(&mut self, mv: Move) {
+        let us = self.turn;
+        self.history.push(Undo {
+            move_: mv,
+            castling_rights: self.castling_rights,
+            en_passant_target: self.en_passant_target,
+            fifty_move_counter: self.fifty_moves,
+            position_key: self.position_key,
+        });
+        self.en_passant_target = None;
+        self.fifty_moves = self.fifty_moves.saturating_add(1);
+
+        if let Some(kind) = mv.drop_kind() {
+            let piece = Piece::new(kind, us);
+            self.set_pocket(piece, self.pocket(piece) - 1);
+            self.put_piece(piece, mv.to());
+            if kind == PieceKind::Pawn {
+                self.fifty_moves = 0;
+            }
+        } else if let Some(side) = mv.castling_side() {
+            self.castle(mv, side);
+        } else if !mv.is_null() {
+            self.move_piece(mv);
+        }
+
+        self.turn = us.opposite();
+        self.ply += 1;
+        if self.variant == Variant::ThreeCheck && !self.checkers().is_empty() {
+            let remaining = self.checks_remaining.get(us).saturating_sub(1);
+            self.set_checks_remaining(us, remaining);
+        }
+    }
+
+    fn castle(&mut self, mv: Move, side: CastlingSide) {
+        let rank = mv.from().rank;
+        let king = self
+            .remove_piece(mv.from())
+            .expect("castling without a king");
+        let rook = self.remove_piece(mv.to()).expect("castling without a rook");
+        let rook_file = match side {
+            CastlingSide::King => File::F,
+            CastlingSide::Queen => File::D,
+        };
+
+        self.put_piece(king, mv.king_destination().unwrap());
+        self.put_piece(rook, Position::new(rook_file, rank));
+        self.castling_rights.clear_color(king.color);
+    }
+
+    fn move_piece(&mut self, mv: Move) {
+        let (from, to) = (mv.from(), mv.to());
+        let piece = self.remove_piece(from).expect("no piece to move");
+        let was_promoted = self.promoted.is_set(from.to_64_index());
+        self.promoted.clear(from.to_64_index());
+
+        if mv.is_capture() {
+            let target = if mv.is_en_passant() {
+                Position::new(to.file, from.rank)
+            } else {
+                to
+            };
+            if self.variant.has_pockets()
+                && let Some(pocketed) = self.captured_to_pocket(target)
+            {
+                self.add_to_pocket(pocketed);
+            }
+            self.remove_piece(target);
+            self.promoted.clear(target.to_64_index());
+            self.fifty_moves = 0;
+        }
+
+        let placed = match mv.promotion_kind() {
+            Some(kind) => Piece::new(kind, piece.color),
+            None => piece,
+        };
+        self.put_piece(placed, to);
+        if self.variant.has_pockets() && (was_promoted || mv.promotion_kind().is_some()) {
+            self.promoted.set(to.to_64_index());
+        }
+
+        match piece.kind {
+            PieceKind::King => self.castling_rights.clear_color(piece.color),
+            PieceKind::Pawn => self.fifty_moves = 0,
+            _ => {}
+        }
+        if mv.is_double_push() {
+            let rank = Rank::iter().nth((from.rank as usize + to.rank as usize) / 2);
+            self.en_passant_target = rank.map(|rank| Position::new(from.file, rank));
+        }
+
+        self.revoke_castling(from);
+        self.revoke_castling(to);
+        if self.variant == Variant::Atomic && mv.is_capture() {
+            for index in self.explosion(to).iter() {
+                let position = Position::from_64_index(index);
+                self.remove_piece(position);
+                self.promoted.clear(index);
+                self.revoke_castling(position);
+            }
+        }
+    }
+
+    /// Drops the castling right of a rook that moved from, or was taken
+    /// or blown up on, `position`.
+    fn revoke_castling(&mut self, position: Position) {
+        for (color, back_rank) in [(Color::White, Rank::One), (Color::Black, Rank::Eight)] {
+            if position.rank != back_rank {
+                continue;
+            }
+            for side in [CastlingSide::King, CastlingSide::Queen] {
+                if self.castling_rights.rook_file(color, side) == Some(position.file) {
+                    self.castling_rights.set(color, side, None);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::{Board, Color};
+    use crate::moves::Move;
+    use crate::variant::{Outcome, Variant};
+
+    fn play(board: &mut Board, moves: &[&str]) {
+        for text in moves {
+            let mv = Move::from_uci(text, board).unwrap_or_else(|| panic!("{}", text));
+            board.make_move(mv);
+        }
+    }
+
+    fn after(fen: &str, variant: Variant, moves: &[&str]) -> String {
+        let mut board = Board::from_variant_fen(fen, variant).unwrap();
+        play(&mut board, moves);
+        board.to_fen()
+    }
+
+    #[test]
+    fn test_opening_moves() {
+        let mut board = Board::new();
+        play(&mut board, &["e2e4", "c7c5", "g1f3"]);
+
+        assert_eq!(
+            board.to_fen(),
+            "rnbqkbnr/pp1ppppp/8/2p5/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2"
+        );
+        assert_eq!(board.history.len(), 3);
+        assert_eq!(
+            board.position_key,
+            Board::from_fen(&board.to_fen()).unwrap().position_key
+        );
+        assert_eq!(
+            board.pawn_key,
+            Board::from_fen(&board.to_fen()).unwrap().pawn_key
+        );
+    }
+
+    #[test]
+    fn test_castling_and_rights() {
+        let fen = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+        assert_eq!(
+            after(fen, Variant::Standard, &["e1g1"]),
+            "r3k2r/8/8/8/8/8/8/R4RK1 b kq - 1 1"
+        );
+        assert_eq!(
+            after(fen, Variant::Standard, &["e1g1", "e8a8"]),
+            "2kr3r/8/8/8/8/8/8/R4RK1 w - - 2 2"
+        );
+        assert_eq!(
+            after(fen, Variant::Standard, &["a1a8"]),
+            "R3k2r/8/8/8/8/8/8/4K2R b Kk - 0 1"
+        );
+
+        // Chess960: the king stays on b1 and the rook goes from a1 to d1.
+        let fen = "1r4kr/8/8/8/8/8/8/RK5R w AHbh - 0 1";
+        assert_eq!(
+            after(fen, Variant::Standard, &["b1a1"]),
+            "1r4kr/8/8/8/8/8/8/2KR3R b kq - 1 1"
+        );
+    }
+
+    #[test]
+    fn test_en_passant_and_promotion() {
+        let fen = "4k3/1P6/8/8/3p4/8/4P3/4K3 w - - 0 1";
+        assert_eq!(
+            after(fen, Variant::Standard, &["e2e4"]),
+            "4k3/1P6/8/8/3pP3/8/8/4K3 b - e3 0 1"
+        );
+        assert_eq!(
+            after(fen, Variant::Standard, &["e2e4", "d4e3"]),
+            "4k3/1P6/8/8/8/4p3/8/4K3 w - - 0 2"
+        );
+        assert_eq!(
+            after(fen, Variant::Standard, &["b7b8n"]),
+            "1N2k3/8/8/8/3p4/8/4P3/4K3 b - - 0 1"
+        );
+    }
+
+    #[test]
+    fn test_crazyhouse_pockets() {
+        let fen = "4k3/8/8/8/8/8/8/4K3[Qp] w - - 0 1";
+        assert_eq!(
+            after(fen, Variant::Crazyhouse, &["Q@e7"]),
+            "4k3/4Q3/8/8/8/8/8/4K3[p] b - - 1 1"
+        );
+        assert_eq!(
+            after(fen, Variant::Crazyhouse, &["Q@e7", "e8e7"]),
+            "8/4k3/8/8/8/8/8/4K3[qp] w - - 0 2"
+        );
+
+        // A promoted piece goes back to the pocket as a pawn.
+        let fen = "3rk3/1P6/8/8/8/8/8/4K3[] w - - 0 1";
+        assert_eq!(
+            after(fen, Variant::Crazyhouse, &["b7b8q"]),
+            "1Q~1rk3/8/8/8/8/8/8/4K3[] b - - 0 1"
+        );
+        assert_eq!(
+            after(fen, Variant::Crazyhouse, &["b7b8q", "d8b8"]),
+            "1r2k3/8/8/8/8/8/8/4K3[p] w - - 0 2"
+        );
+    }
+
+    #[test]
+    fn test_atomic_explosion() {
+        // The knights blow up; the pawn on d5 survives.
+        let fen = "4k3/8/8/3pn3/8/5N2/8/4K3 w - - 0 1";
+        assert_eq!(
+            after(fen, Variant::Atomic, &["f3e5"]),
+            "4k3/8/8/3p4/8/8/8/4K3 b - - 0 1"
+        );
+
+        // The rook on a8 is caught in the blast and loses its right too.
+        let fen = "r3k3/n7/8/8/8/8/8/R3K3 w Qq - 0 1";
+        assert_eq!(
+            after(fen, Variant::Atomic, &["a1a7"]),
+            "4k3/8/8/8/8/8/8/4K3 b - - 0 1"
+        );
+
+        let mut board =
+            Board::from_variant_fen("8/8/4k3/3pn3/8/5N2/8/4K3 w - - 0 1", Variant::Atomic).unwrap();
+        play(&mut board, &["f3e5"]);
+        assert_eq!(board.king(Color::Black), None);
+        assert_eq!(board.outcome(), Some(Outcome::Win(Color::White)));
+    }
+
+    #[test]
+    fn test_three_check_count() {
+        let fen = "4k3/8/8/8/8/8/8/R3K3 w - - 3+3 0 1";
+        let mut board = Board::from_variant_fen(fen, Variant::ThreeCheck).unwrap();
+        play(&mut board, &["a1a8"]);
+        assert_eq!(board.checks_remaining.white, 2);
+        assert_eq!(board.checks_remaining.black, 3);
+    }
+}
@@ -0,0 +1,650 @@
+use std::fmt::Display;
+
+use crate::board::{Board, CastlingSide, Color, Piece, PieceKind, Position};
+use crate::shared::{File, Rank};
+
+/// A move packed into 16 bits:
+///
+/// | bits  | content                       |
+/// |-------|-------------------------------|
+/// | 0-5   | from square (a1 = 0, h8 = 63) |
+/// | 6-11  | to square                     |
+/// | 12-15 | flags, see below              |
+///
+/// | flags | move                                       |
+/// |-------|--------------------------------------------|
+/// | 0     | quiet                                      |
+/// | 1     | double pawn push                           |
+/// | 2     | castle                                     |
+/// | 3     | drop, `PieceKind` in the from bits         |
+/// | 4     | capture                                    |
+/// | 5     | en passant capture                         |
+/// | 8-11  | promotion to knight, bishop, rook, queen   |
+/// | 12-15 | capture with promotion, same order         |
+///
+/// Bit 2 of the flags is set exactly for captures. Castling moves store
+/// the castling rook's square as `to`, so they stay unambiguous in
+/// Chess960 and the side follows from which way the rook is. `Move::NULL`
+/// (all zero bits) is never a legal move.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Move(u16);
+
+impl Move {
+    pub const NULL: Move = Move(0);
+
+    pub const QUIET: u16 = 0;
+    pub const DOUBLE_PUSH: u16 = 1;
+    pub const CASTLE: u16 = 2;
+    pub const DROP: u16 = 3;
+    pub const CAPTURE: u16 = 4;
+    pub const EN_PASSANT: u16 = 5;
+    const PROMOTION: u16 = 8;
+
+    pub fn new(from: Position, to: Position, flags: u16) -> Self {
+        debug_assert!(flags < 16);
+        Move(from.to_64_index() as u16 | (to.to_64_index() as u16) << 6 | flags << 12)
+    }
+
+    /// Promotion to `kind`, which must be a knight, bishop, rook or queen.
+    pub fn promotion(from: Position, to: Position, kind: PieceKind, capture: bool) -> Self {
+        debug_assert!(!matches!(kind, PieceKind::Pawn | PieceKind::King));
+        let mut flags = Move::PROMOTION | (kind as u16 - PieceKind::Knight as u16);
+        if capture {
+            flags |= Move::CAPTURE;
+        }
+        Move::new(from, to, flags)
+    }
+
+    /// Drop of a piece of `kind` from the pocket, for crazyhouse.
+    pub fn drop(kind: PieceKind, to: Position) -> Self {
+        debug_assert!(kind != PieceKind::King);
+        Move(kind as u16 | (to.to_64_index() as u16) << 6 | Move::DROP << 12)
+    }
+
+    /// Parses drop notation such as `P@e4` (the piece letter may be in
+    /// either case).
+    pub fn parse_drop(notation: &str) -> Option<Self> {
+        let mut chars = notation.chars();
+        let kind = chars.next().and_then(PieceKind::from_char)?;
+        if kind == PieceKind::King || chars.next() != Some('@') {
+            return None;
+        }
+        let to = chars.as_str().parse().ok()?;
+
+        Some(Move::drop(kind, to))
+    }
+
+    /// Reads UCI notation for a move on `board`, taking capture, double
+    /// push, en passant and castling from the position. Castling may be
+    /// written as the king's move (`e1g1`) or king-takes-rook (`e1h1`).
+    /// `None` if the text does not fit the position: no piece of the side
+    /// to move on the from square, a drop missing from the pocket, or a
+    /// castle without the right. Legality is not checked.
+    pub fn from_uci(text: &str, board: &Board) -> Option<Self> {
+        if text == "0000" {
+            return Some(Move::NULL);
+        }
+        if text.contains('@') {
+            let mv = Move::parse_drop(text)?;
+            let piece = Piece::new(mv.drop_kind()?, board.turn);
+            let fits = board.pocket(piece) > 0 && board.piece_at(mv.to()).is_none();
+            return fits.then_some(mv);
+        }
+
+        let from: Position = text.get(0..2)?.parse().ok()?;
+        let to: Position = text.get(2..4)?.parse().ok()?;
+        let piece = board
+            .piece_at(from)
+            .filter(|piece| piece.color == board.turn)?;
+        let target = board.piece_at(to);
+        let capture = target.is_some_and(|target| target.color != piece.color);
+
+        match &text[4..] {
+            "" => {}
+            promotion => {
+                let mut chars = promotion.chars();
+                let kind = chars.next().and_then(PieceKind::from_char)?;
+                let last_rank = match piece.color {
+                    Color::White => Rank::Eight,
+                    Color::Black => Rank::One,
+                };
+                if chars.next().is_some()
+                    || matches!(kind, PieceKind::Pawn | PieceKind::King)
+                    || piece.kind != PieceKind::Pawn
+                    || to.rank != last_rank
+                {
+                    return None;
+                }
+                return Some(Move::promotion(from, to, kind, capture));
+            }
+        }
+
+        if piece.kind == PieceKind::King && from.rank == to.rank {
+            let onto_rook = target == Some(Piece::new(PieceKind::Rook, piece.color));
+            let king_form =
+                matches!(to.file, File::C | File::G) && from.file.distance(to.file) >= 2;
+            if onto_rook || king_form {
+                let side = if to.file > from.file {
+                    CastlingSide::King
+                } else {
+                    CastlingSide::Queen
+                };
+                let rook_file = board.castling_rights.rook_file(piece.color, side)?;
+                if !king_form && rook_file != to.file {
+                    return None;
+                }
+                return Some(Move::new(
+                    from,
+                    Position::new(rook_file, from.rank),
+                    Move::CASTLE,
+                ));
+            }
+        }
+        if target.is_some() && !capture {
+            return None;
+        }
+
+        let flags = match piece.kind {
+            PieceKind::Pawn if from.file != to.file && target.is_none() => {
+                if board.en_passant_target != Some(to) {
+                    return None;
+                }
+                Move::EN_PASSANT
+            }
+            PieceKind::Pawn if from.rank.distance(to.rank) == 2 => Move::DOUBLE_PUSH,
+            _ if capture => Move::CAPTURE,
+            _ => Move::QUIET,
+        };
+        Some(Move::new(from, to, flags))
+    }
+
+    /// Wraps `bits` without checking them; see `try_from_bits`.
+    pub fn from_bits(bits: u16) -> Self {
+        Move(bits)
+    }
+
+    /// Wraps `bits` if they encode a move some position could have: a
+    /// known flag code, a pocket kind for drops, and squares on the ranks
+    /// the flags call for. Whether it is legal still depends on the board.
+    pub fn try_from_bits(bits: u16) -> Option<Self> {
+        let mv = Move(bits);
+        if mv.is_null() {
+            return Some(mv);
+        }
+
+        let (from, to) = (mv.0 as usize & 0x3f, mv.0 as usize >> 6 & 0x3f);
+        let (from_rank, to_rank) = (from / 8, to / 8);
+        let valid = match mv.flags() {
+            Move::DROP => {
+                from <= PieceKind::Queen as usize && (from > 0 || (1..7).contains(&to_rank))
+            }
+            _ if from == to => false,
+            Move::QUIET | Move::CAPTURE => true,
+            Move::DOUBLE_PUSH => {
+                from % 8 == to % 8 && matches!((from_rank, to_rank), (1, 3) | (6, 4))
+            }
+            Move::CASTLE => from_rank == to_rank && matches!(from_rank, 0 | 7),
+            Move::EN_PASSANT => {
+                from % 8 != to % 8 && matches!((from_rank, to_rank), (4, 5) | (3, 2))
+            }
+            flags if flags & Move::PROMOTION != 0 => {
+                matches!((from_rank, to_rank), (6, 7) | (1, 0))
+            }
+            _ => false,
+        };
+        valid.then_some(mv)
+    }
+
+    pub fn bits(self) -> u16 {
+        self.0
+    }
+
+    /// Origin square. Meaningless for drops.
+    pub fn from(self) -> Position {
+        Position::from_64_index((self.0 & 0x3f) as usize)
+    }
+
+    pub fn to(self) -> Position {
+        Position::from_64_index((self.0 >> 6 & 0x3f) as usize)
+    }
+
+    pub fn flags(self) -> u16 {
+        self.0 >> 12
+    }
+
+    pub fn is_null(self) -> bool {
+        self == Move::NULL
+    }
+
+    pub fn is_capture(self) -> bool {
+        self.flags() & Move::CAPTURE != 0
+    }
+
+    pub fn is_drop(self) -> bool {
+        self.flags() == Move::DROP
+    }
+
+    pub fn drop_kind(self) -> Option<PieceKind> {
+        if !self.is_drop() {
+            return None;
+        }
+
+        Some(match self.0 & 0x3f {
+            0 => PieceKind::Pawn,
+            1 => PieceKind::Knight,
+            2 => PieceKind::Bishop,
+            3 => PieceKind::Rook,
+            _ => PieceKind::Queen,
+        })
+    }
+
+    pub fn is_en_passant(self) -> bool {
+        self.flags() == Move::EN_PASSANT
+    }
+
+    pub fn is_double_push(self) -> bool {
+        self.flags() == Move::DOUBLE_PUSH
+    }
+
+    pub fn castling_side(self) -> Option<CastlingSide> {
+        if self.flags() != Move::CASTLE {
+            return None;
+        }
+
+        Some(if self.to().file > self.from().file {
+            CastlingSide::King
+        } else {
+            CastlingSide::Queen
+        })
+    }
+
+    /// Where the king ends up when castling: the g- or c-file of its own
+    /// rank.
+    pub fn king_destination(self) -> Option<Position> {
+        let file = match self.castling_side()? {
+            CastlingSide::King => File::G,
+            CastlingSide::Queen => File::C,
+        };
+        Some(Position::new(file, self.from().rank))
+    }
+
+    /// Display adapter for `UCI_Chess960` notation.
+    pub fn chess960(self) -> Chess960Notation {
+        Chess960Notation(self)
+    }
+
+    pub fn promotion_kind(self) -> Option<PieceKind> {
+        if self.flags() & Move::PROMOTION == 0 {
+            return None;
+        }
+
+        Some(match self.flags() & 3 {
+            0 => PieceKind::Knight,
+            1 => PieceKind::Bishop,
+            2 => PieceKind::Rook,
+            _ => PieceKind::Queen,
+        })
+    }
+}
+
+/// Long algebraic notation as used by UCI, e.g. `e2e4`, `e7e8q` or `N@f3`.
+/// Castling is written as the king's move, `e1g1`, unless the king moves
+/// less than two files, as it can in Chess960; then it is king-takes-rook
+/// so the text cannot be read as a plain king move. `0000` is the null
+/// move. Use `chess960` for the `UCI_Chess960` form.
+impl Display for Move {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_uci(*self, false, f)
+    }
+}
+
+/// A move shown as under `UCI_Chess960`, where castling is written
+/// king-takes-rook (`e1h1`) so it stays unambiguous.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Chess960Notation(pub Move);
+
+impl Display for Chess960Notation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_uci(self.0, true, f)
+    }
+}
+
+fn write_uci(mv: Move, chess960: bool, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    if mv.is_null() {
+        return write!(f, "0000");
+    }
+    if let Some(kind) = mv.drop_kind() {
+        return write!(f, "{}@{}", kind.to_char().to_ascii_uppercase(), mv.to());
+    }
+
+    let to = match mv.king_destination() {
+        Some(king_to) if !chess960 && mv.from().file.distance(king_to.file) >= 2 => king_to,
+        _ => mv.to(),
+    };
+    write!(f, "{}{}", mv.from(), to)?;
+    if let Some(kind) = mv.promotion_kind() {
+        write!(f, "{}", kind.to_char())?;
+    }
+    Ok(())
+}
+
+/// Capacity of a `MoveList`, above the 218 moves of the richest known
+/// standard position.
+pub const MAX_MOVES: usize = 256;
+
+/// Room for the 218 moves of the richest standard position plus a drop of
+/// each of the five pocket kinds on each of the 62 squares not holding a
+/// king.
+pub const MAX_CRAZYHOUSE_MOVES: usize = 218 + 5 * 62;
+
+/// A move with its ordering score.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ScoredMove {
+    pub mv: Move,
+    pub score: i32,
+}
+
+/// Fixed-capacity move list on the stack, holding up to `N` moves.
+#[derive(Clone, Debug)]
+pub struct MoveList<const N: usize = MAX_MOVES> {
+    moves: [ScoredMove; N],
+    len: usize,
+}
+
+/// Move list with room for crazyhouse drops next to the board moves.
+pub type CrazyhouseMoveList = MoveList<MAX_CRAZYHOUSE_MOVES>;
+
+impl<const N: usize> Default for MoveList<N> {
+    fn default() -> Self {
+        MoveList {
+            moves: [ScoredMove::default(); N],
+            len: 0,
+        }
+    }
+}
+
+impl MoveList {
+    pub fn new() -> Self {
+        MoveList::default()
+    }
+}
+
+impl<const N: usize> MoveList<N> {
+    pub fn push(&mut self, mv: Move) {
+        self.push_scored(mv, 0);
+    }
+
+    pub fn push_scored(&mut self, mv: Move, score: i32) {
+        self.moves[self.len] = ScoredMove { mv, score };
+        self.len += 1;
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    pub fn as_slice(&self) -> &[ScoredMove] {
+        &self.moves[..self.len]
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [ScoredMove] {
+        &mut self.moves[..self.len]
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Move> + '_ {
+        self.as_slice().iter().map(|scored| scored.mv)
+    }
+
+    pub fn contains(&self, mv: Move) -> bool {
+        self.iter().any(|other| other == mv)
+    }
+
+    /// Moves the best-scored move among `index..` to `index` and returns
+    /// it. Selecting lazily like this is cheaper than sorting when a
+    /// cutoff comes early.
+    pub fn pick(&mut self, index: usize) -> Move {
+        let best = (index..self.len)
+            .max_by_key(|&i| (self.moves[i].score, std::cmp::Reverse(i)))
+            .expect("pick past the end of the move list");
+        self.moves.swap(index, best);
+        self.moves[index].mv
+    }
+
+    /// Sorts by score, best first, keeping generation order among equals.
+    pub fn sort(&mut self) {
+        self.as_mut_slice()
+            .sort_by_key(|scored| std::cmp::Reverse(scored.score));
+    }
+}
+
+impl<const N: usize> std::ops::Index<usize> for MoveList<N> {
+    type Output = ScoredMove;
+
+    fn index(&self, index: usize) -> &ScoredMove {
+        &self.as_slice()[index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(name: &str) -> Position {
+        name.parse().unwrap()
+    }
+
+    #[test]
+    fn test_move_encoding() {
+        let push = Move::new(square("e2"), square("e4"), Move::DOUBLE_PUSH);
+
+        assert_eq!(push.from(), square("e2"));
+        assert_eq!(push.to(), square("e4"));
+        assert!(push.is_double_push());
+        assert!(!push.is_capture());
+        assert_eq!(push.promotion_kind(), None);
+        assert_eq!(push.bits(), 12 | 28 << 6 | 1 << 12);
+        assert_eq!(Move::from_bits(push.bits()), push);
+
+        let en_passant = Move::new(square("e5"), square("d6"), Move::EN_PASSANT);
+        assert!(en_passant.is_capture());
+        assert!(en_passant.is_en_passant());
+
+        let castle = Move::new(square("e1"), square("h1"), Move::CASTLE);
+        assert_eq!(castle.castling_side(), Some(CastlingSide::King));
+        assert!(!castle.is_capture());
+
+        let long = Move::new(square("e8"), square("a8"), Move::CASTLE);
+        assert_eq!(long.castling_side(), Some(CastlingSide::Queen));
+    }
+
+    #[test]
+    fn test_promotions() {
+        for kind in [
+            PieceKind::Knight,
+            PieceKind::Bishop,
+            PieceKind::Rook,
+            PieceKind::Queen,
+        ] {
+            for capture in [false, true] {
+                let mv = Move::promotion(square("b7"), square("a8"), kind, capture);
+                assert_eq!(mv.promotion_kind(), Some(kind));
+                assert_eq!(mv.is_capture(), capture);
+                assert!(!mv.is_en_passant());
+                assert_eq!(mv.castling_side(), None);
+            }
+        }
+    }
+
+    #[test]
+    fn test_move_display() {
+        assert_eq!(
+            Move::new(square("g1"), square("f3"), Move::QUIET).to_string(),
+            "g1f3"
+        );
+        assert_eq!(
+            Move::promotion(square("e7"), square("e8"), PieceKind::Queen, false).to_string(),
+            "e7e8q"
+        );
+        assert_eq!(Move::NULL.to_string(), "0000");
+    }
+
+    #[test]
+    fn test_castling_notation() {
+        let short = Move::new(square("e1"), square("h1"), Move::CASTLE);
+        let long = Move::new(square("e8"), square("a8"), Move::CASTLE);
+
+        assert_eq!(short.king_destination(), Some(square("g1")));
+        assert_eq!(short.to_string(), "e1g1");
+        assert_eq!(long.to_string(), "e8c8");
+        assert_eq!(short.chess960().to_string(), "e1h1");
+        assert_eq!(long.chess960().to_string(), "e8a8");
+
+        // A king moving less than two files is written king-takes-rook.
+        let near = Move::new(square("b1"), square("a1"), Move::CASTLE);
+        assert_eq!(near.to_string(), "b1a1");
+        let far = Move::new(square("b1"), square("h1"), Move::CASTLE);
+        assert_eq!(far.to_string(), "b1g1");
+
+        // Only castling differs between the two forms.
+        let push = Move::new(square("e2"), square("e4"), Move::DOUBLE_PUSH);
+        assert_eq!(push.king_destination(), None);
+        assert_eq!(push.chess960().to_string(), push.to_string());
+    }
+
+    #[test]
+    fn test_from_uci() {
+        let board = Board::from_fen("r3k2r/1P6/8/3pP3/8/8/4P3/R3K2R w KQkq d6 0 1").unwrap();
+        let cases = [
+            ("e2e3", Move::new(square("e2"), square("e3"), Move::QUIET)),
+            (
+                "e2e4",
+                Move::new(square("e2"), square("e4"), Move::DOUBLE_PUSH),
+            ),
+            (
+                "e5d6",
+                Move::new(square("e5"), square("d6"), Move::EN_PASSANT),
+            ),
+            ("a1a8", Move::new(square("a1"), square("a8"), Move::CAPTURE)),
+            ("e1g1", Move::new(square("e1"), square("h1"), Move::CASTLE)),
+            ("e1h1", Move::new(square("e1"), square("h1"), Move::CASTLE)),
+            ("e1c1", Move::new(square("e1"), square("a1"), Move::CASTLE)),
+            (
+                "b7a8q",
+                Move::promotion(square("b7"), square("a8"), PieceKind::Queen, true),
+            ),
+            (
+                "b7b8n",
+                Move::promotion(square("b7"), square("b8"), PieceKind::Knight, false),
+            ),
+            ("0000", Move::NULL),
+        ];
+        for (text, mv) in cases {
+            assert_eq!(Move::from_uci(text, &board), Some(mv), "{}", text);
+        }
+
+        for bad in [
+            "e7e5", "a8a1", "d5d4", "e1f1x", "e2e3q", "b7b8k", "e5f6", "a1e1", "e2", "N@f3",
+        ] {
+            assert_eq!(Move::from_uci(bad, &board), None, "{}", bad);
+        }
+
+        // Chess960 castling where the king moves one file or not at all.
+        let board = Board::from_fen("1r4kr/8/8/8/8/8/8/RK5R w AHbh - 0 1").unwrap();
+        let castle = Move::new(square("b1"), square("a1"), Move::CASTLE);
+        assert_eq!(Move::from_uci(&castle.to_string(), &board), Some(castle));
+        let castle = Move::new(square("b1"), square("h1"), Move::CASTLE);
+        assert_eq!(Move::from_uci(&castle.to_string(), &board), Some(castle));
+
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/4K3[Np] w - - 0 1").unwrap();
+        assert_eq!(Move::from_uci("N@f3", &board), Move::parse_drop("N@f3"));
+        assert_eq!(Move::from_uci("P@f3", &board), None);
+        assert_eq!(Move::from_uci("N@e1", &board), None);
+    }
+
+    #[test]
+    fn test_try_from_bits() {
+        let moves = [
+            Move::new(square("e2"), square("e4"), Move::DOUBLE_PUSH),
+            Move::new(square("e1"), square("a1"), Move::CASTLE),
+            Move::new(square("d5"), square("e6"), Move::EN_PASSANT),
+            Move::promotion(square("b2"), square("a1"), PieceKind::Queen, true),
+            Move::parse_drop("Q@h8").unwrap(),
+            Move::NULL,
+        ];
+        for mv in moves {
+            assert_eq!(Move::try_from_bits(mv.bits()), Some(mv));
+        }
+
+        let invalid = [
+            6 << 12,
+            7 << 12 | 1,
+            Move::drop(PieceKind::Pawn, square("e8")).bits(),
+            Move::DROP << 12 | 5,
+            Move::new(square("e4"), square("e4"), Move::QUIET).bits(),
+            Move::new(square("e2"), square("e5"), Move::DOUBLE_PUSH).bits(),
+            Move::new(square("e1"), square("h2"), Move::CASTLE).bits(),
+            Move::new(square("e4"), square("d5"), Move::EN_PASSANT).bits(),
+            Move::promotion(square("e6"), square("e7"), PieceKind::Rook, false).bits(),
+        ];
+        for bits in invalid {
+            assert_eq!(Move::try_from_bits(bits), None, "{:#06x}", bits);
+        }
+    }
+
+    #[test]
+    fn test_drops() {
+        let drop = Move::parse_drop("N@f3").unwrap();
+
+        assert!(drop.is_drop());
+        assert!(!drop.is_capture());
+        assert_eq!(drop.flags() & Move::CAPTURE, 0);
+        assert_eq!(drop.drop_kind(), Some(PieceKind::Knight));
+        assert_eq!(drop.to(), square("f3"));
+        assert_eq!(drop.promotion_kind(), None);
+        assert_eq!(drop.to_string(), "N@f3");
+        assert_eq!(Move::parse_drop("p@e4").unwrap().to_string(), "P@e4");
+
+        for bad in ["K@e4", "Pe4", "P@e9", "P@e44", "X@e4"] {
+            assert_eq!(Move::parse_drop(bad), None, "{}", bad);
+        }
+        assert_eq!(
+            Move::new(square("e2"), square("e4"), Move::DOUBLE_PUSH).drop_kind(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_move_list_ordering() {
+        let a = Move::new(square("a2"), square("a3"), Move::QUIET);
+        let b = Move::new(square("b2"), square("b3"), Move::QUIET);
+        let c = Move::new(square("c2"), square("c3"), Move::QUIET);
+
+        let mut list = MoveList::new();
+        assert!(list.is_empty());
+        list.push_scored(a, 10);
+        list.push_scored(b, 30);
+        list.push_scored(c, 30);
+        assert_eq!(list.len(), 3);
+        assert!(list.contains(c));
+
+        assert_eq!(list.pick(0), b);
+        assert_eq!(list.pick(1), c);
+        assert_eq!(list.pick(2), a);
+
+        let mut sorted = MoveList::new();
+        sorted.push_scored(a, -5);
+        sorted.push_scored(b, 7);
+        sorted.push(c);
+        sorted.sort();
+        assert_eq!(sorted.iter().collect::<Vec<_>>(), vec![b, c, a]);
+        assert_eq!(sorted[0].score, 7);
+    }
+}
@@ -0,0 +1,404 @@
+//! Configurable terminal rendering of a `Board`.
+
+use std::io::IsTerminal;
+
+use crate::board::{Board, Color, Piece, Position};
+use crate::shared::SquareIndex;
+
+/// How squares and pieces are drawn.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Style {
+    /// 24-bit background and piece colours.
+    TrueColor,
+    /// The xterm 256-colour palette, for terminals without truecolor.
+    Ansi256,
+    /// No colours; outline glyphs for white, filled for black.
+    Unicode,
+    /// No colours; FEN letters, `KQRBNP` for white and `kqrbnp` for black.
+    Ascii,
+}
+
+impl Style {
+    /// Picks a style for stdout: no colours when `NO_COLOR` is set or
+    /// stdout is not a terminal, truecolor when `COLORTERM` advertises it,
+    /// 256 colours otherwise.
+    pub fn detect() -> Self {
+        Style::from_env(
+            std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()),
+            std::env::var("COLORTERM").ok().as_deref(),
+            std::io::stdout().is_terminal(),
+        )
+    }
+
+    /// Style by its command-line name: `truecolor`, `256`, `unicode` or
+    /// `ascii`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "truecolor" => Some(Style::TrueColor),
+            "256" => Some(Style::Ansi256),
+            "unicode" => Some(Style::Unicode),
+            "ascii" => Some(Style::Ascii),
+            _ => None,
+        }
+    }
+
+    fn from_env(no_color: bool, colorterm: Option<&str>, terminal: bool) -> Self {
+        if no_color || !terminal {
+            Style::Unicode
+        } else if matches!(colorterm, Some("truecolor" | "24bit")) {
+            Style::TrueColor
+        } else {
+            Style::Ansi256
+        }
+    }
+
+    fn is_colored(self) -> bool {
+        matches!(self, Style::TrueColor | Style::Ansi256)
+    }
+}
+
+const RESET: &str = "\x1b[0m";
+
+/// Board renderer with its display options, e.g.
+/// `BoardRenderer::new(Style::Ascii).flipped(true).render(&board)`.
+#[derive(Clone, Debug)]
+pub struct BoardRenderer {
+    style: Style,
+    flipped: bool,
+    coordinates: bool,
+    highlights: Vec<Position>,
+    arrows: Vec<(Position, Position)>,
+    annotations: Vec<(Position, char)>,
+    tags: Vec<(Position, char)>,
+}
+
+impl BoardRenderer {
+    pub fn new(style: Style) -> Self {
+        BoardRenderer {
+            style,
+            flipped: false,
+            coordinates: true,
+            highlights: Vec::new(),
+            arrows: Vec::new(),
+            annotations: Vec::new(),
+            tags: Vec::new(),
+        }
+    }
+
+    /// Draws the board from black's side.
+    pub fn flipped(mut self, flipped: bool) -> Self {
+        self.flipped = flipped;
+        self
+    }
+
+    pub fn coordinates(mut self, coordinates: bool) -> Self {
+        self.coordinates = coordinates;
+        self
+    }
+
+    pub fn highlight(mut self, position: Position) -> Self {
+        self.highlights.push(position);
+        self
+    }
+
+    /// Highlights both ends and marks the empty squares in between. Arrows
+    /// that are not along a rank, file or diagonal only get the ends.
+    pub fn arrow(mut self, from: Position, to: Position) -> Self {
+        self.arrows.push((from, to));
+        self
+    }
+
+    /// Shows `note`, e.g. an attacker count, in the right-hand corner of
+    /// the square.
+    pub fn annotate(mut self, position: Position, note: char) -> Self {
+        self.annotations.push((position, note));
+        self
+    }
+
+    /// Shows `tag`, e.g. a mark for a pinned piece, in the left-hand corner
+    /// of the square. Without colours it takes the place of the highlight
+    /// bracket.
+    pub fn tag(mut self, position: Position, tag: char) -> Self {
+        self.tags.push((position, tag));
+        self
+    }
+
+    pub fn render(&self, board: &Board) -> String {
+        let mut marks: [Option<char>; 64] = [None; 64];
+        let mut notes: [Option<char>; 64] = [None; 64];
+        let mut tags: [Option<char>; 64] = [None; 64];
+        let mut highlighted = [false; 64];
+
+        for &(position, note) in &self.annotations {
+            notes[position.to_64_index()] = Some(note);
+        }
+        for &(position, tag) in &self.tags {
+            tags[position.to_64_index()] = Some(tag);
+        }
+        for &position in &self.highlights {
+            highlighted[position.to_64_index()] = true;
+        }
+        for &(from, to) in &self.arrows {
+            highlighted[from.to_64_index()] = true;
+            highlighted[to.to_64_index()] = true;
+            if let Some((path, glyph)) = arrow_path(from, to) {
+                let glyph = if self.style == Style::Ascii {
+                    glyph.1
+                } else {
+                    glyph.0
+                };
+                for square in path {
+                    marks[square] = Some(glyph);
+                }
+            }
+        }
+
+        let order = |i: usize| if self.flipped { 7 - i } else { i };
+        let mut output = String::new();
+
+        for row in 0..8 {
+            let rank = 7 - order(row);
+            if self.coordinates {
+                output.push_str(&format!("{} ", rank + 1));
+            }
+
+            for column in 0..8 {
+                let file = order(column);
+                let square = rank * 8 + file;
+                let piece = board.piece_at(Position::from_64_index(square));
+                output.push_str(&self.cell(
+                    square,
+                    piece,
+                    marks[square],
+                    notes[square],
+                    tags[square],
+                    highlighted[square],
+                ));
+            }
+
+            output.push('\n');
+        }
+
+        if self.coordinates {
+            output.push_str("  ");
+            for column in 0..8 {
+                let file = (b'a' + order(column) as u8) as char;
+                output.push_str(&format!(" {} ", file));
+            }
+            output.push('\n');
+        }
+
+        output
+    }
+
+    fn cell(
+        &self,
+        square: SquareIndex,
+        piece: Option<Piece>,
+        mark: Option<char>,
+        note: Option<char>,
+        tag: Option<char>,
+        highlighted: bool,
+    ) -> String {
+        let is_light = (square % 8 + square / 8) % 2 == 1;
+
+        if !self.style.is_colored() {
+            let content = match (piece, mark) {
+                (Some(piece), _) if self.style == Style::Ascii => piece.to_char().to_string(),
+                (Some(piece), _) => piece.kind.to_unicode(piece.color).to_string(),
+                (None, Some(mark)) => mark.to_string(),
+                (None, None) => ".".to_string(),
+            };
+            let (left, right) = if highlighted { ('[', ']') } else { (' ', ' ') };
+            return format!(
+                "{}{}{}",
+                tag.unwrap_or(left),
+                content,
+                note.unwrap_or(right)
+            );
+        }
+
+        let background = match (self.style, is_light, highlighted) {
+            (Style::TrueColor, true, false) => "48;2;180;180;180",
+            (Style::TrueColor, false, false) => "48;2;120;120;120",
+            (Style::TrueColor, true, true) => "48;2;205;210;106",
+            (Style::TrueColor, false, true) => "48;2;170;162;58",
+            (_, true, false) => "48;5;250",
+            (_, false, false) => "48;5;244",
+            (_, true, true) => "48;5;186",
+            (_, false, true) => "48;5;143",
+        };
+        let (foreground, content) = match (piece, mark) {
+            // The filled glyphs are used for both sides here and told apart
+            // by colour, which reads better on a coloured board.
+            (Some(piece), _) => (
+                match (self.style, piece.color) {
+                    (Style::TrueColor, Color::White) => "1;38;2;255;255;255",
+                    (Style::TrueColor, Color::Black) => "1;38;2;0;0;0",
+                    (_, Color::White) => "1;38;5;231",
+                    (_, Color::Black) => "1;38;5;16",
+                },
+                piece.kind.to_unicode(Color::Black).to_string(),
+            ),
+            (None, Some(mark)) => ("38;5;88", mark.to_string()),
+            (None, None) => ("39", " ".to_string()),
+        };
+
+        let tag = tag.unwrap_or(' ');
+        match note {
+            Some(note) => format!(
+                "\x1b[{};{}m{}{}\x1b[22;38;5;21m{}{}",
+                background, foreground, tag, content, note, RESET
+            ),
+            None => format!(
+                "\x1b[{};{}m{}{} {}",
+                background, foreground, tag, content, RESET
+            ),
+        }
+    }
+}
+
+/// Squares strictly between `from` and `to` along a line, with the
+/// Unicode and ASCII glyphs for the direction, as seen from white's side.
+fn arrow_path(from: Position, to: Position) -> Option<(Vec<SquareIndex>, (char, char))> {
+    let (from, to) = (from.to_64_index() as isize, to.to_64_index() as isize);
+    let file_step = (to % 8 - from % 8).signum();
+    let rank_step = (to / 8 - from / 8).signum();
+    let (files, ranks) = ((to % 8 - from % 8).abs(), (to / 8 - from / 8).abs());
+
+    if from == to || (files != 0 && ranks != 0 && files != ranks) {
+        return None;
+    }
+
+    let glyph = match (file_step, rank_step) {
+        (1, 0) => ('→', '-'),
+        (-1, 0) => ('←', '-'),
+        (0, 1) => ('↑', '|'),
+        (0, -1) => ('↓', '|'),
+        (1, 1) => ('↗', '/'),
+        (-1, -1) => ('↙', '/'),
+        (-1, 1) => ('↖', '\\'),
+        _ => ('↘', '\\'),
+    };
+
+    let step = rank_step * 8 + file_step;
+    let path = (1..files.max(ranks))
+        .map(|i| (from + i * step) as SquareIndex)
+        .collect();
+    Some((path, glyph))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::{File, Rank};
+
+    #[test]
+    fn test_ascii_start_position() {
+        let rendered = BoardRenderer::new(Style::Ascii).render(&Board::new());
+
+        assert_eq!(
+            rendered,
+            "8  r  n  b  q  k  b  n  r \n\
+             7  p  p  p  p  p  p  p  p \n\
+             6  .  .  .  .  .  .  .  . \n\
+             5  .  .  .  .  .  .  .  . \n\
+             4  .  .  .  .  .  .  .  . \n\
+             3  .  .  .  .  .  .  .  . \n\
+             2  P  P  P  P  P  P  P  P \n\
+             1  R  N  B  Q  K  B  N  R \n   \
+             a  b  c  d  e  f  g  h \n"
+        );
+    }
+
+    #[test]
+    fn test_flipped_without_coordinates() {
+        let rendered = BoardRenderer::new(Style::Unicode)
+            .flipped(true)
+            .coordinates(false)
+            .render(&Board::new());
+        let lines: Vec<&str> = rendered.lines().collect();
+
+        assert_eq!(lines.len(), 8);
+        assert_eq!(lines[0], " ♖  ♘  ♗  ♔  ♕  ♗  ♘  ♖ ");
+        assert_eq!(lines[7], " ♜  ♞  ♝  ♚  ♛  ♝  ♞  ♜ ");
+    }
+
+    #[test]
+    fn test_highlights_and_arrows() {
+        let e2 = Position::new(File::E, Rank::Two);
+        let e5 = Position::new(File::E, Rank::Five);
+        let rendered = BoardRenderer::new(Style::Ascii)
+            .arrow(e2, e5)
+            .highlight(Position::new(File::A, Rank::Eight))
+            .render(&Board::new());
+        let lines: Vec<&str> = rendered.lines().collect();
+
+        assert!(lines[0].starts_with("8 [r]"));
+        assert_eq!(&lines[3][14..17], "[.]");
+        assert_eq!(&lines[4][14..17], " | ");
+        assert_eq!(&lines[5][14..17], " | ");
+        assert_eq!(&lines[6][14..17], "[P]");
+    }
+
+    #[test]
+    fn test_annotations() {
+        let rendered = BoardRenderer::new(Style::Ascii)
+            .annotate(Position::new(File::A, Rank::Three), '2')
+            .annotate(Position::new(File::B, Rank::One), '1')
+            .highlight(Position::new(File::B, Rank::One))
+            .render(&Board::new());
+        let lines: Vec<&str> = rendered.lines().collect();
+
+        assert!(lines[5].starts_with("3  .2 . "));
+        assert!(lines[7].starts_with("1  R [N1 B "));
+
+        let tagged = BoardRenderer::new(Style::Ascii)
+            .tag(Position::new(File::C, Rank::One), '*')
+            .tag(Position::new(File::D, Rank::One), '^')
+            .highlight(Position::new(File::D, Rank::One))
+            .render(&Board::new());
+        assert!(tagged.lines().nth(7).unwrap().starts_with("1  R  N *B ^Q]"));
+    }
+
+    #[test]
+    fn test_arrow_path() {
+        let square = |file, rank| Position::new(file, rank);
+
+        let (path, glyph) =
+            arrow_path(square(File::A, Rank::One), square(File::D, Rank::Four)).unwrap();
+        assert_eq!(path, vec![9, 18]);
+        assert_eq!(glyph, ('↗', '/'));
+        assert!(arrow_path(square(File::G, Rank::One), square(File::F, Rank::Three)).is_none());
+    }
+
+    #[test]
+    fn test_colored_styles() {
+        let board = Board::new();
+        let truecolor = BoardRenderer::new(Style::TrueColor).render(&board);
+        let ansi256 = BoardRenderer::new(Style::Ansi256).render(&board);
+
+        assert!(truecolor.contains("\x1b[48;2;"));
+        assert!(!truecolor.contains("\x1b[48;5;"));
+        assert!(ansi256.contains("\x1b[48;5;"));
+        assert!(ansi256.contains("38;5;231"));
+        assert!(ansi256.contains("38;5;16"));
+    }
+
+    #[test]
+    fn test_style_detection() {
+        assert_eq!(
+            Style::from_env(true, Some("truecolor"), true),
+            Style::Unicode
+        );
+        assert_eq!(
+            Style::from_env(false, Some("truecolor"), false),
+            Style::Unicode
+        );
+        assert_eq!(
+            Style::from_env(false, Some("24bit"), true),
+            Style::TrueColor
+        );
+        assert_eq!(Style::from_env(false, None, true), Style::Ansi256);
+    }
+}
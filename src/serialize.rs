@@ -0,0 +1,275 @@
+//! Serde support behind the `serde` feature. Most types derive it; the
+//! ones here are written as the strings players already use: squares as
+//! `e4`, variants by their `UCI_Variant` name, boards as FEN and moves in
+//! UCI notation.
+//!
+//! UCI text needs the position to decode, so moves are only read back
+//! inside a `Game`, by replaying them from its start position.
+
+use std::collections::BTreeMap;
+
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::board::{Board, Position};
+use crate::game::Game;
+use crate::moves::Move;
+use crate::variant::{Outcome, Variant};
+
+impl Serialize for Position {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Position {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
+
+impl Serialize for Variant {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for Variant {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Variant::from_name(&name)
+            .ok_or_else(|| D::Error::custom(format!("unknown variant '{}'", name)))
+    }
+}
+
+/// Moves are written in UCI notation. There is no `Deserialize`: the text
+/// only becomes a `Move` against the position it is played in, see
+/// `Move::from_uci`.
+impl Serialize for Move {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// How a `Board` is written: its FEN, with the variant alongside only when
+/// the FEN cannot show it.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum BoardRecord {
+    Fen(String),
+    WithVariant { variant: Variant, fen: String },
+}
+
+/// Whether a FEN read on its own comes back as `variant`: standard chess,
+/// and crazyhouse and three-check, whose FENs always carry the pocket or
+/// the check count.
+fn fen_shows(variant: Variant) -> bool {
+    matches!(
+        variant,
+        Variant::Standard | Variant::Crazyhouse | Variant::ThreeCheck
+    )
+}
+
+impl Serialize for Board {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let fen = self.to_fen();
+        let record = if fen_shows(self.variant) {
+            BoardRecord::Fen(fen)
+        } else {
+            BoardRecord::WithVariant {
+                variant: self.variant,
+                fen,
+            }
+        };
+        record.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Board {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let board = match BoardRecord::deserialize(deserializer)? {
+            BoardRecord::Fen(fen) => Board::from_fen(&fen),
+            BoardRecord::WithVariant { variant, fen } => Board::from_variant_fen(&fen, variant),
+        };
+        board.map_err(D::Error::custom)
+    }
+}
+
+/// How a `Game` is written: the moves as UCI text, read back by replaying
+/// them from the start position.
+#[derive(Serialize, Deserialize)]
+struct GameRecord {
+    variant: Variant,
+    start_fen: String,
+    moves: Vec<String>,
+    tags: BTreeMap<String, String>,
+    outcome: Option<Outcome>,
+}
+
+impl Serialize for Game {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        GameRecord {
+            variant: self.variant,
+            start_fen: self.start_fen.clone(),
+            moves: self.moves.iter().map(Move::to_string).collect(),
+            tags: self.tags.clone(),
+            outcome: self.outcome,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Game {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let record = GameRecord::deserialize(deserializer)?;
+        let mut game = Game::from_fen(record.variant, &record.start_fen);
+        game.tags = record.tags;
+        game.outcome = record.outcome;
+
+        let mut board = game.start_board().map_err(D::Error::custom)?;
+        for (ply, text) in record.moves.iter().enumerate() {
+            let mv = Move::from_uci(text, &board).ok_or_else(|| {
+                D::Error::custom(format!(
+                    "move {} '{}' does not fit the position",
+                    ply + 1,
+                    text
+                ))
+            })?;
+            board.make_move(mv);
+            game.moves.push(mv);
+        }
+        Ok(game)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{Color, Piece, PieceKind};
+    use crate::shared::{File, Rank};
+
+    fn round_trip<T>(value: &T) -> T
+    where
+        T: Serialize + for<'de> Deserialize<'de>,
+    {
+        serde_json::from_str(&serde_json::to_string(value).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_simple_types() {
+        let square = Position::new(File::E, Rank::Four);
+        let knight = Piece::new(PieceKind::Knight, Color::Black);
+
+        assert_eq!(serde_json::to_string(&square).unwrap(), "\"e4\"");
+        assert_eq!(round_trip(&square), square);
+        assert_eq!(round_trip(&knight), knight);
+        assert_eq!(
+            serde_json::to_string(&Variant::ThreeCheck).unwrap(),
+            "\"3check\""
+        );
+        assert_eq!(
+            round_trip(&Outcome::Win(Color::White)),
+            Outcome::Win(Color::White)
+        );
+
+        let drop = Move::parse_drop("N@f3").unwrap();
+        assert_eq!(serde_json::to_string(&drop).unwrap(), "\"N@f3\"");
+
+        assert!(serde_json::from_str::<Position>("\"e9\"").is_err());
+        assert!(serde_json::from_str::<Variant>("\"bughouse\"").is_err());
+    }
+
+    #[test]
+    fn test_board_as_fen() {
+        let fen = "r1bqk2r/pppp1ppp/2n2n2/4p3/1bB1P3/2N2N2/PPPP1PPP/R1BQK2R[Pn] w KQkq - 0 5";
+        let board = Board::from_fen(fen).unwrap();
+        assert_eq!(
+            serde_json::to_string(&board).unwrap(),
+            format!("\"{}\"", fen)
+        );
+
+        let atomic = Board::new_variant(Variant::Atomic);
+        assert_eq!(
+            serde_json::to_string(&atomic).unwrap(),
+            format!(
+                "{{\"variant\":\"atomic\",\"fen\":\"{}\"}}",
+                Variant::Atomic.start_fen()
+            )
+        );
+
+        let err = serde_json::from_str::<Board>("\"8/8/8 w - - 0 1\"").unwrap_err();
+        assert!(err.to_string().contains("invalid piece placement"));
+    }
+
+    #[test]
+    fn test_board_variant_must_match_fen() {
+        let json = "{\"variant\":\"chess\",\"fen\":\"4k3/8/8/8/8/8/8/4K3[P] w - - 0 1\"}";
+        let err = serde_json::from_str::<Board>(json).unwrap_err();
+        assert!(err.to_string().contains("not describe a chess position"));
+
+        let json = "{\"variant\":\"atomic\",\"fen\":\"4k3/8/8/8/8/8/8/4K3 w - - 2+3 0 1\"}";
+        assert!(serde_json::from_str::<Board>(json).is_err());
+    }
+
+    #[test]
+    fn test_board_round_trip_keeps_variant() {
+        for variant in Variant::ALL {
+            let board = Board::new_variant(variant);
+            let parsed = round_trip(&board);
+
+            assert_eq!(parsed.variant, variant);
+            assert_eq!(parsed.to_fen(), board.to_fen());
+            assert_eq!(parsed.position_key, board.position_key);
+            assert_eq!(parsed.checks_remaining.white, board.checks_remaining.white);
+        }
+    }
+
+    fn game_with(variant: Variant, fen: &str, moves: &[&str]) -> Game {
+        let mut game = Game::from_fen(variant, fen);
+        let mut board = game.start_board().unwrap();
+        for text in moves {
+            let mv = Move::from_uci(text, &board).unwrap();
+            board.make_move(mv);
+            game.moves.push(mv);
+        }
+        game
+    }
+
+    #[test]
+    fn test_game_round_trip() {
+        let mut game = game_with(
+            Variant::Atomic,
+            Variant::Atomic.start_fen(),
+            &["e2e4", "d7d5", "e4d5", "g8f6"],
+        );
+        game.set_tag("Event", "Club championship");
+        game.outcome = Some(Outcome::Draw);
+
+        let json = serde_json::to_string(&game).unwrap();
+        assert!(json.contains("\"variant\":\"atomic\""));
+        assert!(json.contains("\"moves\":[\"e2e4\",\"d7d5\",\"e4d5\",\"g8f6\"]"));
+        assert_eq!(round_trip(&game), game);
+        assert!(game.moves[0].is_double_push());
+        assert!(game.moves[2].is_capture());
+
+        let fen = "r3k2r/8/8/8/8/8/8/R3K2R[] w KQkq - 0 1";
+        let game = game_with(Variant::Crazyhouse, fen, &["e1g1", "a8a1", "f1a1", "R@e7"]);
+        assert_eq!(round_trip(&game), game);
+
+        let fen = "1r4kr/8/8/8/8/8/8/RK5R w AHbh - 0 1";
+        let game = game_with(Variant::Standard, fen, &["b1a1", "g8h8"]);
+        assert_eq!(round_trip(&game), game);
+    }
+
+    #[test]
+    fn test_game_moves_must_fit() {
+        let json = format!(
+            "{{\"variant\":\"chess\",\"start_fen\":\"{}\",\"moves\":[\"e2e4\",\"e2e4\"],\"tags\":{{}},\"outcome\":null}}",
+            crate::fen::START_FEN
+        );
+        let err = serde_json::from_str::<Game>(&json).unwrap_err();
+        assert!(err.to_string().contains("move 2 'e2e4'"));
+    }
+}
@@ -18,6 +18,11 @@ impl File {
         (b'a' + self as u8) as char
     }
 
+    /// How many files apart `self` and `other` are.
+    pub fn distance(self, other: File) -> u8 {
+        (self as u8).abs_diff(other as u8)
+    }
+
     pub fn iter() -> impl Iterator<Item = File> {
         (0..8).map(|i| unsafe { std::mem::transmute::<u8, File>(i) })
     }
@@ -55,6 +60,11 @@ impl Rank {
         (b'1' + self as u8) as char
     }
 
+    /// How many ranks apart `self` and `other` are.
+    pub fn distance(self, other: Rank) -> u8 {
+        (self as u8).abs_diff(other as u8)
+    }
+
     pub fn iter() -> impl DoubleEndedIterator<Item = Rank> {
         (0..8).map(|i| unsafe { std::mem::transmute::<u8, Rank>(i) })
     }
@@ -82,6 +92,12 @@ pub fn file_rank_to_120_index(file: char, rank: char) -> SquareIndex {
 
 #[inline(always)]
 pub fn file_rank_to_64_index(file: char, rank: char) -> SquareIndex {
-    (rank as SquareIndex - '1' as SquareIndex) * 8
-        + (file as SquareIndex - 'a' as SquareIndex + 1)
-}
\ No newline at end of file
+    (rank as SquareIndex - '1' as SquareIndex) * 8 + (file as SquareIndex - 'a' as SquareIndex + 1)
+}
+
+/// Converts a 120-square mailbox index of a playable square to the
+/// 64-square (bitboard) index.
+#[inline(always)]
+pub fn index_120_to_64(index: SquareIndex) -> SquareIndex {
+    (index / 10 - 2) * 8 + index % 10 - 1
+}
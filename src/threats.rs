@@ -0,0 +1,149 @@
+//! Attack and threat summary of a position, for `andrej show --attacks`.
+
+use crate::bitboard::Bitboard;
+use crate::board::{Board, Color, PieceKind, Position};
+use crate::eval::pawns::PawnFeatures;
+use crate::render::BoardRenderer;
+
+#[derive(Clone, Debug)]
+pub struct Threats {
+    pub color: Color,
+    /// Number of `color` pieces attacking each square, a1 = 0.
+    pub attackers: [u32; 64],
+    /// Pieces of either side pinned to their king.
+    pub pinned: Bitboard,
+    /// Enemy pieces `color` attacks that have no defender.
+    pub hanging: Bitboard,
+    /// Passed pawns of either side.
+    pub passed: Bitboard,
+}
+
+impl Threats {
+    /// Threats by `color`.
+    pub fn of(board: &Board, color: Color) -> Self {
+        let enemy = color.opposite();
+        let attackers = std::array::from_fn(|index| {
+            board
+                .attackers_of(Position::from_64_index(index), color)
+                .count()
+        });
+
+        let mut hanging = Bitboard::default();
+        for (position, piece) in board.pieces() {
+            let index = position.to_64_index();
+            if piece.color == enemy
+                && piece.kind != PieceKind::King
+                && attackers[index] > 0
+                && !board.is_square_attacked(position, enemy)
+            {
+                hanging.set(index);
+            }
+        }
+
+        Threats {
+            color,
+            attackers,
+            pinned: board.pinned_pieces(Color::White) | board.pinned_pieces(Color::Black),
+            hanging,
+            passed: PawnFeatures::of(board, Color::White).passed
+                | PawnFeatures::of(board, Color::Black).passed,
+        }
+    }
+
+    /// The board with attacker counts in the corner of each square, hanging
+    /// pieces highlighted and pinned pieces and passed pawns tagged with
+    /// `*` and `^`, followed by a legend.
+    pub fn render(&self, board: &Board, renderer: BoardRenderer) -> String {
+        let mut renderer = renderer;
+        for (index, &count) in self.attackers.iter().enumerate() {
+            if count > 0 {
+                let note = char::from_digit(count, 10).unwrap_or('+');
+                renderer = renderer.annotate(Position::from_64_index(index), note);
+            }
+        }
+        for index in self.hanging.iter() {
+            renderer = renderer.highlight(Position::from_64_index(index));
+        }
+        // A pinned passed pawn shows as pinned.
+        for (squares, tag) in [(self.passed, '^'), (self.pinned, '*')] {
+            for index in squares.iter() {
+                renderer = renderer.tag(Position::from_64_index(index), tag);
+            }
+        }
+
+        let mut output = renderer.render(board);
+        output.push_str(&format!("Attacks by {:?}\n", self.color));
+        for (label, squares) in [
+            ("Pinned (*)", self.pinned),
+            ("Hanging", self.hanging),
+            ("Passed pawns (^)", self.passed),
+        ] {
+            output.push_str(&format!("{}: {}\n", label, square_list(squares)));
+        }
+        output
+    }
+}
+
+fn square_list(squares: Bitboard) -> String {
+    if squares.is_empty() {
+        return "-".to_string();
+    }
+
+    squares
+        .iter()
+        .map(|index| Position::from_64_index(index).to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::Style;
+
+    #[test]
+    fn test_threats() {
+        let board = Board::from_fen("4r1k1/8/1n6/3P4/8/8/4B3/4K2R w - - 0 1").unwrap();
+        let threats = Threats::of(&board, Color::White);
+
+        // The bishop covers d3 and is pinned by the e8 rook; nothing hits h1.
+        assert_eq!(threats.attackers[19], 1);
+        assert_eq!(threats.attackers[7], 0);
+        assert_eq!(square_list(threats.pinned), "e2");
+        assert_eq!(square_list(threats.passed), "d5");
+        assert_eq!(square_list(threats.hanging), "-");
+
+        let black = Threats::of(&board, Color::Black);
+        assert_eq!(square_list(black.hanging), "d5");
+    }
+
+    #[test]
+    fn test_render() {
+        let board = Board::from_fen("4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1").unwrap();
+        let output =
+            Threats::of(&board, Color::White).render(&board, BoardRenderer::new(Style::Ascii));
+        let lines: Vec<&str> = output.lines().collect();
+
+        // e4 attacks d5 and f5; d5 is hanging.
+        assert_eq!(&lines[3][11..14], "[p1");
+        assert_eq!(&lines[3][17..20], " .1");
+        assert_eq!(lines[9], "Attacks by White");
+        assert_eq!(lines[10], "Pinned (*): -");
+        assert_eq!(lines[11], "Hanging: d5");
+        assert_eq!(lines[12], "Passed pawns (^): -");
+    }
+
+    #[test]
+    fn test_render_marks_pins_and_passed_pawns() {
+        let board = Board::from_fen("4r1k1/8/1n6/3P4/8/8/4B3/4K2R w - - 0 1").unwrap();
+        let output =
+            Threats::of(&board, Color::White).render(&board, BoardRenderer::new(Style::Ascii));
+        let lines: Vec<&str> = output.lines().collect();
+
+        // The pinned e2 bishop, defended by the king, and the d5 passer.
+        assert_eq!(&lines[6][14..17], "*B1");
+        assert_eq!(&lines[3][11..14], "^P ");
+        assert_eq!(lines[10], "Pinned (*): e2");
+        assert_eq!(lines[12], "Passed pawns (^): d5");
+    }
+}
@@ -0,0 +1,357 @@
+//! Texel tuning of the handcrafted evaluation weights.
+//!
+//! The evaluation is linear in `EvalParams`, so each position is reduced
+//! once to its feature counts (white minus black). Tuning then fits the
+//! weights so that `sigmoid(eval)` predicts the game results, minimising
+//! the mean squared error with Adam.
+
+use std::fmt::Display;
+
+use crate::board::{Board, Color};
+use crate::datagen::{Record, parse_result};
+use crate::eval::params::{EvalParams, PARAM_COUNT};
+use crate::eval::pawns::PawnTable;
+use crate::eval::{FeatureSink, visit_features};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DatasetError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl Display for DatasetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for DatasetError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TuneError {
+    /// There is nothing to fit the weights to.
+    NoSamples,
+}
+
+impl Display for TuneError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TuneError::NoSamples => write!(f, "no positions to tune on"),
+        }
+    }
+}
+
+impl std::error::Error for TuneError {}
+
+/// Collects feature counts, white positive and black negative.
+struct Coefficients([i32; PARAM_COUNT]);
+
+impl FeatureSink for Coefficients {
+    fn add(&mut self, color: Color, param: usize, count: i32) {
+        match color {
+            Color::White => self.0[param] += count,
+            Color::Black => self.0[param] -= count,
+        }
+    }
+}
+
+/// One labelled position, reduced to its non-zero feature counts.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sample {
+    pub coefficients: Vec<(usize, i32)>,
+    /// Game result from white's point of view: 1, 0.5 or 0.
+    pub result: f64,
+}
+
+impl Sample {
+    pub fn new(board: &Board, result: f64, pawn_table: &mut PawnTable) -> Self {
+        let mut coefficients = Coefficients([0; PARAM_COUNT]);
+        visit_features(board, pawn_table, &mut coefficients);
+
+        Sample {
+            coefficients: coefficients
+                .0
+                .iter()
+                .enumerate()
+                .filter(|(_, count)| **count != 0)
+                .map(|(param, count)| (param, *count))
+                .collect(),
+            result,
+        }
+    }
+
+    /// Evaluation from white's point of view under `weights`.
+    fn evaluate(&self, weights: &[f64]) -> f64 {
+        self.coefficients
+            .iter()
+            .map(|&(param, count)| weights[param] * count as f64)
+            .sum()
+    }
+}
+
+/// Splits a dataset line into its FEN and result. Accepted forms:
+///
+/// - `<fen> [1.0]`, `<fen> [0.5]`, `<fen> [1-0]`
+/// - `<fen> 1-0`, `<fen> 1/2-1/2`
+/// - `<fen> c9 "0-1";` (EPD)
+/// - `<fen> | <eval> | <result>` (`datagen` text records)
+pub fn parse_line(line: &str) -> Result<(Board, f64), String> {
+    let line = line.trim();
+    if line.contains('|') {
+        let record = Record::from_text(line).map_err(|err| err.to_string())?;
+        return Ok((record.board, record.result));
+    }
+
+    // The result is the last token; a crazyhouse pocket inside the FEN is
+    // bracketed too, so brackets alone don't mark it.
+    let (fen, result) = match line.split_once(" c9 ") {
+        Some(split) => split,
+        None => line
+            .rsplit_once(char::is_whitespace)
+            .ok_or("missing game result")?,
+    };
+
+    let result = result.trim_matches(|c: char| c.is_whitespace() || "[]\";".contains(c));
+    let result = parse_result(result).ok_or(format!("invalid game result '{}'", result))?;
+
+    let board = Board::from_fen(fen.trim().trim_end_matches(';')).map_err(|err| err.to_string())?;
+    Ok((board, result))
+}
+
+/// Reads a dataset, one labelled position per line. Blank lines and lines
+/// starting with `#` are ignored.
+///
+/// Positions with the side to move in check are skipped: their static
+/// evaluation says little about the result. The tuner otherwise assumes
+/// the positions are quiet, since there is no quiescence search to resolve
+/// pending captures yet.
+pub fn load_samples(input: &str) -> Result<Vec<Sample>, DatasetError> {
+    let mut pawn_table = PawnTable::default();
+    let mut samples = Vec::new();
+
+    for (index, line) in input.lines().enumerate() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (board, result) = parse_line(line).map_err(|message| DatasetError {
+            line: index + 1,
+            message,
+        })?;
+        if !board.checkers().is_empty() {
+            continue;
+        }
+
+        samples.push(Sample::new(&board, result, &mut pawn_table));
+    }
+
+    Ok(samples)
+}
+
+/// Expected score for a white-relative evaluation in centipawns.
+pub fn sigmoid(eval: f64, k: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-k * eval / 400.0))
+}
+
+pub fn mean_squared_error(samples: &[Sample], weights: &[f64], k: f64) -> f64 {
+    let total: f64 = samples
+        .iter()
+        .map(|sample| (sample.result - sigmoid(sample.evaluate(weights), k)).powi(2))
+        .sum();
+    total / samples.len().max(1) as f64
+}
+
+/// Scaling constant that best fits the current weights to the results, by
+/// golden section search over `0..=10`.
+pub fn find_k(samples: &[Sample], weights: &[f64]) -> f64 {
+    let ratio = (5f64.sqrt() - 1.0) / 2.0;
+    let error = |k| mean_squared_error(samples, weights, k);
+    let (mut low, mut high) = (0.0, 10.0);
+
+    for _ in 0..60 {
+        let a = high - ratio * (high - low);
+        let b = low + ratio * (high - low);
+        if error(a) < error(b) {
+            high = b;
+        } else {
+            low = a;
+        }
+    }
+
+    (low + high) / 2.0
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct TuneOptions {
+    pub epochs: usize,
+    pub learning_rate: f64,
+}
+
+impl Default for TuneOptions {
+    fn default() -> Self {
+        TuneOptions {
+            epochs: 1000,
+            learning_rate: 1.0,
+        }
+    }
+}
+
+/// Full-batch Adam over all parameters. Parameters that no sample
+/// exercises get no gradient and keep their starting values.
+/// `progress` is called after every epoch with the epoch and error.
+pub fn tune(
+    samples: &[Sample],
+    start: &EvalParams,
+    options: TuneOptions,
+    mut progress: impl FnMut(usize, f64),
+) -> Result<EvalParams, TuneError> {
+    const BETA1: f64 = 0.9;
+    const BETA2: f64 = 0.999;
+    const EPSILON: f64 = 1e-8;
+
+    if samples.is_empty() {
+        return Err(TuneError::NoSamples);
+    }
+
+    let mut weights: Vec<f64> = start.0.iter().map(|&w| w as f64).collect();
+    let k = find_k(samples, &weights);
+    let mut m = vec![0.0; PARAM_COUNT];
+    let mut v = vec![0.0; PARAM_COUNT];
+
+    for epoch in 1..=options.epochs {
+        let mut gradient = vec![0.0; PARAM_COUNT];
+        for sample in samples {
+            let s = sigmoid(sample.evaluate(&weights), k);
+            // d/dw (r - s)^2, with ds/de = s (1 - s) k ln(10) / 400.
+            let delta = -2.0 * (sample.result - s) * s * (1.0 - s) * k * 10f64.ln() / 400.0;
+            for &(param, count) in &sample.coefficients {
+                gradient[param] += delta * count as f64;
+            }
+        }
+
+        for param in 0..PARAM_COUNT {
+            let g = gradient[param] / samples.len() as f64;
+            m[param] = BETA1 * m[param] + (1.0 - BETA1) * g;
+            v[param] = BETA2 * v[param] + (1.0 - BETA2) * g * g;
+            let m_hat = m[param] / (1.0 - BETA1.powi(epoch as i32));
+            let v_hat = v[param] / (1.0 - BETA2.powi(epoch as i32));
+            weights[param] -= options.learning_rate * m_hat / (v_hat.sqrt() + EPSILON);
+        }
+
+        progress(epoch, mean_squared_error(samples, &weights, k));
+    }
+
+    let mut params = EvalParams::default();
+    for (param, weight) in params.0.iter_mut().zip(&weights) {
+        *param = weight.round() as i32;
+    }
+    Ok(params)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::params::{BISHOP_PAIR, ROOK_OPEN_FILE};
+    use crate::eval::{Evaluate, Evaluator};
+
+    const MIDDLEGAME: &str = "r1bq1rk1/pp2bppp/2n1pn2/3p4/2PP4/2N1PN2/PP3PPP/R2QKB1R w KQ - 0 8";
+
+    #[test]
+    fn test_parse_line() {
+        let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+        assert_eq!(parse_line(&format!("{} [1.0]", start)).unwrap().1, 1.0);
+        assert_eq!(parse_line(&format!("{} [0.5]", start)).unwrap().1, 0.5);
+        assert_eq!(parse_line(&format!("{} 0-1", start)).unwrap().1, 0.0);
+        assert_eq!(parse_line(&format!("{} 1/2-1/2", start)).unwrap().1, 0.5);
+        assert_eq!(parse_line(&format!("{} | 15 | 0.0", start)).unwrap().1, 0.0);
+
+        let (board, result) =
+            parse_line("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - c9 \"1-0\";").unwrap();
+        assert_eq!(board.to_fen(), start);
+        assert_eq!(result, 1.0);
+
+        let crazyhouse =
+            "r1bqk2r/pppp1ppp/2n2n2/4p3/1bB1P3/2N2N2/PPPP1PPP/R1BQK2R[Pn] w KQkq - 0 5";
+        for label in ["1-0", "[1.0]"] {
+            let (board, result) = parse_line(&format!("{} {}", crazyhouse, label)).unwrap();
+            assert_eq!(board.to_fen(), crazyhouse);
+            assert_eq!(result, 1.0);
+        }
+
+        assert!(parse_line(start).is_err());
+        assert!(parse_line("8/8/8/8 w - - [1.0]").is_err());
+    }
+
+    #[test]
+    fn test_coefficients_match_evaluation() {
+        let board = Board::from_fen(MIDDLEGAME).unwrap();
+        let params = EvalParams::default();
+        let weights: Vec<f64> = params.0.iter().map(|&w| w as f64).collect();
+        let sample = Sample::new(&board, 1.0, &mut PawnTable::default());
+
+        assert_eq!(
+            sample.evaluate(&weights) as i32,
+            Evaluator::new().evaluate(&board)
+        );
+    }
+
+    #[test]
+    fn test_load_samples_skips_checks() {
+        let samples = load_samples(
+            "# comment\n\
+             4k3/8/8/8/8/8/8/R3K3 w - - [1.0]\n\
+             \n\
+             4k3/8/8/8/8/8/8/4R1K1 b - - [1.0]\n",
+        )
+        .unwrap();
+
+        assert_eq!(samples.len(), 1);
+        assert_eq!(load_samples("\n4k3 w - - [1.0]").unwrap_err().line, 2);
+    }
+
+    #[test]
+    fn test_tuning_fits_results() {
+        // Material is level; the side with the bishop pair or the rook on
+        // the open file always wins.
+        let samples = load_samples(
+            "2b1kn2/8/8/8/8/8/8/2B1KB2 w - - [1.0]\n\
+             2b1kb2/8/8/8/8/8/8/2B1KN2 w - - [0.0]\n\
+             r3k3/p7/8/8/8/8/P7/4K2R w - - [1.0]\n\
+             4k2r/p7/8/8/8/8/P7/R3K3 w - - [0.0]\n\
+             4k3/8/8/8/8/8/8/4K3 w - - [0.5]\n",
+        )
+        .unwrap();
+        let start = EvalParams::default();
+        let weights: Vec<f64> = start.0.iter().map(|&w| w as f64).collect();
+        let k = find_k(&samples, &weights);
+
+        let mut errors = Vec::new();
+        let tuned = tune(&samples, &start, TuneOptions::default(), |_, error| {
+            errors.push(error)
+        })
+        .unwrap();
+        let tuned_weights: Vec<f64> = tuned.0.iter().map(|&w| w as f64).collect();
+
+        assert_eq!(errors.len(), TuneOptions::default().epochs);
+        assert!(
+            mean_squared_error(&samples, &tuned_weights, k)
+                < mean_squared_error(&samples, &weights, k)
+        );
+        assert!(tuned[BISHOP_PAIR] > start[BISHOP_PAIR]);
+        assert!(tuned[ROOK_OPEN_FILE] > start[ROOK_OPEN_FILE]);
+    }
+
+    #[test]
+    fn test_tuning_without_samples() {
+        let samples = load_samples("# nothing but comments\n").unwrap();
+        let result = tune(
+            &samples,
+            &EvalParams::default(),
+            TuneOptions::default(),
+            |_, _| panic!("no epoch should run"),
+        );
+
+        assert_eq!(result, Err(TuneError::NoSamples));
+    }
+}
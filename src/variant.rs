@@ -0,0 +1,242 @@
+use crate::bitboard::Bitboard;
+use crate::board::{Board, Color};
+use crate::fen::START_FEN;
+
+/// Rule set a `Board` is played under.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Variant {
+    #[default]
+    Standard,
+    /// Captured pieces go to the capturer's pocket and can be dropped back
+    /// on the board instead of moving.
+    Crazyhouse,
+    /// Captures explode, removing the capturing piece and every non-pawn
+    /// piece next to the capture square. Exploding the enemy king wins.
+    Atomic,
+    /// Bringing the king to d4, d5, e4 or e5 wins.
+    KingOfTheHill,
+    /// Giving the third check wins.
+    ThreeCheck,
+    /// Captures are compulsory, the king is an ordinary piece and losing
+    /// every piece wins.
+    Antichess,
+    /// White has 36 pawns and no king, and loses once they are all gone.
+    Horde,
+}
+
+impl Variant {
+    pub const ALL: [Variant; 7] = [
+        Variant::Standard,
+        Variant::Crazyhouse,
+        Variant::Atomic,
+        Variant::KingOfTheHill,
+        Variant::ThreeCheck,
+        Variant::Antichess,
+        Variant::Horde,
+    ];
+
+    /// Name as used by the `UCI_Variant` option.
+    pub fn name(self) -> &'static str {
+        match self {
+            Variant::Standard => "chess",
+            Variant::Crazyhouse => "crazyhouse",
+            Variant::Atomic => "atomic",
+            Variant::KingOfTheHill => "kingofthehill",
+            Variant::ThreeCheck => "3check",
+            Variant::Antichess => "antichess",
+            Variant::Horde => "horde",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "chess" | "standard" => Some(Variant::Standard),
+            "crazyhouse" => Some(Variant::Crazyhouse),
+            "atomic" => Some(Variant::Atomic),
+            "kingofthehill" | "koth" => Some(Variant::KingOfTheHill),
+            "3check" | "threecheck" => Some(Variant::ThreeCheck),
+            "antichess" | "giveaway" => Some(Variant::Antichess),
+            "horde" => Some(Variant::Horde),
+            _ => None,
+        }
+    }
+
+    pub fn has_pockets(self) -> bool {
+        self == Variant::Crazyhouse
+    }
+
+    /// Whether `color` must have exactly one king on the board.
+    pub fn requires_king(self, color: Color) -> bool {
+        match self {
+            Variant::Antichess => false,
+            Variant::Horde => color == Color::Black,
+            _ => true,
+        }
+    }
+
+    /// Whether a king can leave the board during the game, as in atomic
+    /// when it is caught in an explosion. Such a position still has at
+    /// most one king per side.
+    pub fn king_can_vanish(self) -> bool {
+        self == Variant::Atomic
+    }
+
+    pub fn start_fen(self) -> &'static str {
+        match self {
+            Variant::Crazyhouse => "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR[] w KQkq - 0 1",
+            Variant::ThreeCheck => "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 3+3 0 1",
+            Variant::Antichess => "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w - - 0 1",
+            Variant::Horde => {
+                "rnbqkbnr/pppppppp/8/1PP2PP1/PPPPPPPP/PPPPPPPP/PPPPPPPP/PPPPPPPP w kq - 0 1"
+            }
+            _ => START_FEN,
+        }
+    }
+}
+
+/// How a game ended.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Outcome {
+    Win(Color),
+    Draw,
+}
+
+/// d4, e4, d5 and e5.
+const CENTER: Bitboard = Bitboard(0x0000_0018_1800_0000);
+
+impl Board {
+    /// The start position of `variant`.
+    pub fn new_variant(variant: Variant) -> Self {
+        Board::from_variant_fen(variant.start_fen(), variant).expect("valid start position")
+    }
+
+    /// The result if the game is over by a rule that can be read off the
+    /// position: a variant's win condition or the fifty-move rule.
+    /// Checkmate and stalemate need the move generator and are not
+    /// detected.
+    pub fn outcome(&self) -> Option<Outcome> {
+        for color in [Color::White, Color::Black] {
+            let enemy = color.opposite();
+            let won = match self.variant {
+                Variant::KingOfTheHill => self
+                    .king(color)
+                    .is_some_and(|king| CENTER.is_set(king.to_64_index())),
+                Variant::ThreeCheck => *self.checks_remaining.get(color) == 0,
+                Variant::Atomic => self.king(enemy).is_none(),
+                Variant::Antichess => self.occupancy(color).is_empty(),
+                Variant::Horde => color == Color::Black && self.occupancy(enemy).is_empty(),
+                _ => false,
+            };
+            if won {
+                return Some(Outcome::Win(color));
+            }
+        }
+
+        if self.fifty_moves >= 100 {
+            return Some(Outcome::Draw);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fen::FenError;
+
+    #[test]
+    fn test_variant_names() {
+        for variant in Variant::ALL {
+            assert_eq!(Variant::from_name(variant.name()), Some(variant));
+        }
+        assert_eq!(Variant::from_name("Crazyhouse"), Some(Variant::Crazyhouse));
+        assert_eq!(Variant::from_name("bughouse"), None);
+    }
+
+    #[test]
+    fn test_king_of_the_hill() {
+        let fen = "8/8/8/3K4/8/8/8/4k3 b - - 0 1";
+        assert_eq!(Board::from_fen(fen).unwrap().outcome(), None);
+
+        let board = Board::from_variant_fen(fen, Variant::KingOfTheHill).unwrap();
+        assert_eq!(board.outcome(), Some(Outcome::Win(Color::White)));
+
+        let board =
+            Board::from_variant_fen("8/8/2K5/8/8/5k2/8/8 w - - 0 1", Variant::KingOfTheHill)
+                .unwrap();
+        assert_eq!(board.outcome(), None);
+    }
+
+    #[test]
+    fn test_three_check() {
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 2+0 0 1").unwrap();
+        assert_eq!(board.outcome(), Some(Outcome::Win(Color::Black)));
+
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 1+1 0 1").unwrap();
+        assert_eq!(board.outcome(), None);
+    }
+
+    #[test]
+    fn test_atomic_exploded_king() {
+        let board = Board::from_variant_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1", Variant::Atomic);
+        assert_eq!(board.unwrap().outcome(), None);
+
+        let board = Board::from_variant_fen("8/8/8/8/8/8/8/4K3 b - - 0 1", Variant::Atomic);
+        assert_eq!(board.unwrap().outcome(), Some(Outcome::Win(Color::White)));
+
+        assert_eq!(
+            Board::from_fen("8/8/8/8/8/8/8/4K3 b - - 0 1").err(),
+            Some(FenError::MissingKing(Color::Black))
+        );
+        assert!(
+            Board::from_variant_fen("4kk2/8/8/8/8/8/8/4K3 w - - 0 1", Variant::Atomic).is_err()
+        );
+    }
+
+    #[test]
+    fn test_antichess() {
+        let board = Board::new_variant(Variant::Antichess);
+        assert_eq!(board.king(Color::White), None);
+        assert!(board.castling_rights.is_empty());
+        assert_eq!(board.outcome(), None);
+
+        // Two black kings and none for white are fine; white has nothing
+        // left and wins.
+        let board =
+            Board::from_variant_fen("k6k/8/8/8/8/8/8/8 b - - 0 40", Variant::Antichess).unwrap();
+        assert_eq!(board.outcome(), Some(Outcome::Win(Color::White)));
+        assert!(board.checkers().is_empty());
+    }
+
+    #[test]
+    fn test_horde() {
+        let board = Board::new_variant(Variant::Horde);
+        let white = board.occupancy(Color::White);
+
+        assert_eq!(white.count(), 36);
+        assert_eq!(board.pawns.white.count(), 36);
+        assert_eq!(board.king(Color::White), None);
+        assert_eq!(board.to_fen(), Variant::Horde.start_fen());
+        assert_eq!(board.outcome(), None);
+
+        let lost = Board::from_variant_fen("4k3/8/8/8/8/8/8/8 w - - 0 60", Variant::Horde).unwrap();
+        assert_eq!(lost.outcome(), Some(Outcome::Win(Color::Black)));
+        assert!(Board::from_variant_fen("8/8/8/8/8/8/8/PPPP4 w - - 0 1", Variant::Horde).is_err());
+    }
+
+    #[test]
+    fn test_start_positions() {
+        for variant in Variant::ALL {
+            let board = Board::new_variant(variant);
+            assert_eq!(board.variant, variant);
+            assert_eq!(board.to_fen(), variant.start_fen());
+        }
+    }
+
+    #[test]
+    fn test_fifty_move_draw() {
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 100 80").unwrap();
+        assert_eq!(board.outcome(), Some(Outcome::Draw));
+    }
+}
@@ -0,0 +1,101 @@
+use crate::board::{Color, Piece};
+use crate::shared::SquareIndex;
+
+/// SplitMix64 step, usable in const context so the key tables are fixed
+/// at compile time and hashes are identical across runs and machines.
+const fn split_mix(state: u64) -> (u64, u64) {
+    let state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    (state, z ^ (z >> 31))
+}
+
+/// One key per piece (6 kinds x 2 colours) per square, indexed with
+/// `piece_index` and the 64-square index.
+pub const PIECE_KEYS: [[u64; 64]; 12] = {
+    let mut keys = [[0; 64]; 12];
+    let mut state = 0x616e_6472_656a;
+    let mut piece = 0;
+    while piece < 12 {
+        let mut square = 0;
+        while square < 64 {
+            let (next, key) = split_mix(state);
+            state = next;
+            keys[piece][square] = key;
+            square += 1;
+        }
+        piece += 1;
+    }
+    keys
+};
+
+/// Largest pocket count with its own key. More pieces of one kind than
+/// this cannot be in a crazyhouse pocket.
+pub const MAX_POCKET: usize = 16;
+
+/// One key per droppable piece (5 kinds x 2 colours, `piece_index` order
+/// without the kings) per pocket count. A pocket holding `n` pieces of a
+/// kind is hashed by the key for `n`, so count 0 contributes nothing.
+pub const POCKET_KEYS: [[u64; MAX_POCKET + 1]; 10] = {
+    let mut keys = [[0; MAX_POCKET + 1]; 10];
+    let mut state = 0x706f_636b_6574;
+    let mut piece = 0;
+    while piece < 10 {
+        let mut count = 1;
+        while count <= MAX_POCKET {
+            let (next, key) = split_mix(state);
+            state = next;
+            keys[piece][count] = key;
+            count += 1;
+        }
+        piece += 1;
+    }
+    keys
+};
+
+pub fn piece_index(piece: Piece) -> usize {
+    let offset = match piece.color {
+        Color::White => 0,
+        Color::Black => 6,
+    };
+    offset + piece.kind as usize
+}
+
+pub fn piece_key(piece: Piece, index: SquareIndex) -> u64 {
+    PIECE_KEYS[piece_index(piece)][index]
+}
+
+/// Key for `count` pieces like `piece` in its owner's pocket.
+pub fn pocket_key(piece: Piece, count: u8) -> u64 {
+    let index = match piece.color {
+        Color::White => 0,
+        Color::Black => 5,
+    } + piece.kind as usize;
+    POCKET_KEYS[index][count as usize]
+}
+
+/// One key per colour per number of checks still to give at three-check.
+/// The starting count of 3 has no key, so boards of other variants hash
+/// the same as before.
+pub const CHECK_KEYS: [[u64; 4]; 2] = {
+    let mut keys = [[0; 4]; 2];
+    let mut state = 0x0063_6865_636b;
+    let mut color = 0;
+    while color < 2 {
+        let mut count = 0;
+        while count < 3 {
+            let (next, key) = split_mix(state);
+            state = next;
+            keys[color][count] = key;
+            count += 1;
+        }
+        color += 1;
+    }
+    keys
+};
+
+/// Key for `color` having `remaining` checks left to give.
+pub fn check_key(color: Color, remaining: u8) -> u64 {
+    CHECK_KEYS[color as usize][remaining as usize]
+}